@@ -1,25 +1,50 @@
+pub mod block;
 pub mod cbc;
 pub mod ctr;
 pub mod ecb;
+pub mod key;
+pub mod padding;
 
-use crate::util;
+use crate::{error::Error, oracle::EncryptionOracle, util};
+use key::{AesKey, KeySize};
 use rand::{distributions::Uniform, thread_rng, Rng};
 
 /* ======== RANDOM AES MODE ======== */
-pub fn detect_aes_ecb_from_oracle(oracle: impl Fn(&[u8]) -> Vec<u8>) -> bool {
+pub fn detect_aes_ecb_from_oracle(oracle: &impl EncryptionOracle) -> Result<bool, Error> {
     let bytes = [b'\0'; 16 * 3];
-    let encrypted = oracle(&bytes);
+    let encrypted = oracle.encrypt(&bytes)?;
 
-    &encrypted[16..32] == &encrypted[32..48]
+    Ok(encrypted[16..32] == encrypted[32..48])
 }
 
 // Encrypts data under a random key, with either EBC or CBC mode (chosen randomly)
 pub fn random_aes_mode_oracle(bytes: &[u8]) -> Vec<u8> {
-    let mut rng = thread_rng();
+    random_aes_mode_encrypt(bytes).0
+}
+
+/// Same as the oracle, but every random choice comes from the given RNG.
+///
+/// ```
+/// use cryptopals::{aes, mt19937::MT19937};
+/// use rand::SeedableRng;
+///
+/// // A seeded MT19937 makes runs reproducible
+/// let encrypt = |seed| aes::random_aes_mode_oracle_with_rng(&[0; 48], &mut MT19937::seed_from_u64(seed));
+/// assert_eq!(encrypt(1), encrypt(1));
+/// assert_ne!(encrypt(1), encrypt(2));
+/// ```
+pub fn random_aes_mode_oracle_with_rng(bytes: &[u8], rng: &mut impl Rng) -> Vec<u8> {
+    random_aes_mode_encrypt_with_rng(bytes, rng).0
+}
 
-    // Generate random 16-byte key
-    let mut key = [0u8; 16];
-    rng.fill(&mut key);
+// Same as the oracle, but also returns whether ECB was used so the detection can be checked
+pub fn random_aes_mode_encrypt(bytes: &[u8]) -> (Vec<u8>, bool) {
+    random_aes_mode_encrypt_with_rng(bytes, &mut thread_rng())
+}
+
+pub fn random_aes_mode_encrypt_with_rng(bytes: &[u8], rng: &mut impl Rng) -> (Vec<u8>, bool) {
+    // Generate random key, of any of the AES key sizes
+    let key = AesKey::random_with_rng(KeySize::ALL[rng.gen_range(0..KeySize::ALL.len())], rng);
 
     // Generate random bytes to add to beginning and end of plaintext
     let add_len_range: Uniform<usize> = Uniform::new(5, 11);
@@ -31,65 +56,139 @@ pub fn random_aes_mode_oracle(bytes: &[u8]) -> Vec<u8> {
     bytes_modified.extend_from_slice(bytes);
     bytes_modified.append(&mut append);
 
-    let encrypted = if rng.gen() {
+    let is_ecb: bool = rng.gen();
+    let encrypted = if is_ecb {
         // Use ECB half the time
-        print!("ECB: ");
-        ecb::encrypt_aes_ecb(&bytes_modified, &key)
+        ecb::encrypt_aes_ecb(&bytes_modified, key.as_bytes()).unwrap()
     } else {
         // Use CBC half the time
-        print!("CBC: ");
         let mut iv = [0u8; 16];
         rng.fill(&mut iv);
-        cbc::encrypt_aes_cbc(&bytes_modified, &key, &iv)
+        cbc::encrypt_aes_cbc(&bytes_modified, key.as_bytes(), &iv).unwrap()
     };
 
-    encrypted
+    (encrypted, is_ecb)
 }
 
-/* ======== UTIL ======== */
-pub fn pad_pkcs7(message: &[u8], block_size: usize) -> Vec<u8> {
-    let mut pad_length = block_size - message.len() % block_size;
-    if pad_length == 0 {
-        pad_length = block_size;
+/* ======== BITFLIPPING ======== */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BitflipMode {
+    // Flips the previous ciphertext block, which scrambles that block's plaintext
+    Cbc,
+    // Flips the ciphertext in place, leaving the rest of the plaintext untouched
+    Ctr,
+}
+
+/// Modifies the ciphertext so that the known plaintext at offset decrypts to the target instead.
+/// The offset is into the plaintext, so under CBC it can't be in the first block, which would
+/// need the IV flipped instead.
+///
+/// ```
+/// use cryptopals::{aes::{self, BitflipMode}, Error};
+///
+/// let mut ciphertext = [0u8; 48];
+/// aes::flip_known_plaintext(&mut ciphertext, BitflipMode::Cbc, 20, b"user", b"root")?;
+/// assert_eq!(&ciphertext[4..8], &[b'u' ^ b'r', b's' ^ b'o', b'e' ^ b'o', b'r' ^ b't']);
+///
+/// assert_eq!(
+///     aes::flip_known_plaintext(&mut ciphertext, BitflipMode::Cbc, 4, b"user", b"root"),
+///     Err(Error::InvalidOffset { offset: 4 })
+/// );
+/// assert_eq!(
+///     aes::flip_known_plaintext(&mut ciphertext, BitflipMode::Ctr, 46, b"user", b"root"),
+///     Err(Error::InvalidOffset { offset: 46 })
+/// );
+/// assert_eq!(
+///     aes::flip_known_plaintext(&mut ciphertext, BitflipMode::Ctr, 0, b"user", b"admin"),
+///     Err(Error::LengthMismatch { expected: 4, len: 5 })
+/// );
+/// # Ok::<(), cryptopals::Error>(())
+/// ```
+pub fn flip_known_plaintext(
+    ciphertext: &mut [u8],
+    mode: BitflipMode,
+    offset: usize,
+    known: &[u8],
+    target: &[u8],
+) -> Result<(), Error> {
+    if known.len() != target.len() {
+        return Err(Error::LengthMismatch {
+            expected: known.len(),
+            len: target.len(),
+        });
+    }
+
+    let start = match mode {
+        BitflipMode::Cbc => offset.checked_sub(16),
+        BitflipMode::Ctr => Some(offset),
+    };
+    let flipped = start
+        .and_then(|start| ciphertext.get_mut(start..start.checked_add(known.len())?))
+        .ok_or(Error::InvalidOffset { offset })?;
+
+    for (byte, (&known_byte, &target_byte)) in flipped.iter_mut().zip(known.iter().zip(target)) {
+        *byte ^= known_byte ^ target_byte;
     }
 
-    assert!(pad_length < 256);
+    Ok(())
+}
+
+/* ======== UTIL ======== */
+// Always adds between 1 and block_size bytes, so a message that is already aligned gets a whole block
+pub fn pad_pkcs7(message: &[u8], block_size: usize) -> Result<Vec<u8>, Error> {
+    padding::check_block_size(block_size)?;
+    let pad_length = block_size - message.len() % block_size;
 
     let mut padded = Vec::from(message);
-    padded.append(&mut vec![pad_length as u8; pad_length]);
+    padded.resize(message.len() + pad_length, pad_length as u8);
 
-    padded
+    Ok(padded)
 }
 
-pub fn strip_pkcs7(message: &[u8]) -> Result<Vec<u8>, String> {
+pub fn strip_pkcs7(message: &[u8]) -> Result<Vec<u8>, Error> {
     if validate_pkcs7(message) {
         Ok(Vec::from(
             &message[..message.len() - *(message.last().unwrap()) as usize],
         ))
     } else {
-        Err("Invalid PKCS#7 padding".to_string())
+        Err(Error::BadPadding)
     }
 }
 
-fn validate_pkcs7(message: &[u8]) -> bool {
-    if message.last() == Some(&0) {
+/// Checks PKCS#7 padding without branching on the message, looking at the last 255 bytes
+/// (or all of them, if fewer) whatever the padding byte is, so the time taken doesn't reveal
+/// where the padding went wrong.
+///
+/// ```
+/// use cryptopals::aes;
+///
+/// assert!(aes::validate_pkcs7(b"ICE ICE BABY\x04\x04\x04\x04"));
+/// assert!(aes::validate_pkcs7(b"\x01"));
+/// assert!(!aes::validate_pkcs7(b"ICE ICE BABY\x05\x05\x05\x05"));
+/// assert!(!aes::validate_pkcs7(b"ICE ICE BABY\x01\x02\x03\x04"));
+/// assert!(!aes::validate_pkcs7(b"ICE ICE BABY\0"));
+/// assert!(!aes::validate_pkcs7(b"\x02"));
+/// assert!(!aes::validate_pkcs7(b""));
+/// ```
+pub fn validate_pkcs7(message: &[u8]) -> bool {
+    let Some(&padding) = message.last() else {
         return false;
-    }
+    };
+    let checked = message.len().min(255) as u16;
 
-    if let Some(&padding) = message.last() {
-        if message.len() >= padding.into() {
-            return message
-                .iter()
-                .rev()
-                .take(padding.into())
-                .all(|&b| b == padding);
-        }
+    // Masks are 0xff where the condition holds, from the borrow out of a u16 subtraction
+    let lt = |a: u16, b: u16| (a.wrapping_sub(b) >> 8) as u8;
+
+    // Zero padding, or more padding than message
+    let mut bad = lt(padding as u16, 1) | lt(checked, padding as u16);
+    for (i, &b) in message.iter().rev().take(255).enumerate() {
+        bad |= lt(i as u16, padding as u16) & (b ^ padding);
     }
 
-    false
+    bad == 0
 }
 
-fn encode_to_query_string(query: &[(String, String)], sep: char, middle: char) -> String {
+pub fn encode_to_query_string(query: &[(String, String)], sep: char, middle: char) -> String {
     let metacharacters = [sep, middle];
 
     query
@@ -106,16 +205,13 @@ fn encode_to_query_string(query: &[(String, String)], sep: char, middle: char) -
         .join(&sep.to_string())
 }
 
-pub fn parse_query_string(str: &str) -> Vec<(String, String)> {
+pub fn parse_query_string(str: &str) -> Result<Vec<(String, String)>, Error> {
     str.split_terminator('&')
-        .fold(Vec::new(), |mut acc, query| {
-            if let Some((key, value)) = query.split_once('=') {
-                acc.push((decode_meta(&key), decode_meta(&value)));
-                acc
-            } else {
-                panic!("Invalid query string format")
-            }
+        .map(|query| {
+            let (key, value) = query.split_once('=').ok_or(Error::MalformedQuery)?;
+            Ok((decode_meta(key)?, decode_meta(value)?))
         })
+        .collect()
 }
 
 pub fn encode_meta(str: &str, meta: &[char]) -> String {
@@ -132,16 +228,27 @@ pub fn encode_meta(str: &str, meta: &[char]) -> String {
         .collect()
 }
 
-pub fn decode_meta(query: &str) -> String {
-    let mut decoded = String::new();
+/// Escapes are bytes, so a multi-byte character escaped byte by byte comes back whole.
+///
+/// ```
+/// use cryptopals::{aes, Error};
+///
+/// assert_eq!(aes::decode_meta("caf%C3%A9%3Bx")?, "café;x");
+/// assert_eq!(aes::decode_meta("%C3"), Err(Error::MalformedQuery));
+/// # Ok::<(), cryptopals::Error>(())
+/// ```
+pub fn decode_meta(query: &str) -> Result<String, Error> {
+    let mut decoded = Vec::new();
     let mut i_prev = 0;
     for (i, _) in query.match_indices('%') {
-        decoded.push_str(&query[i_prev..i]);
-        decoded.push(util::hex_to_bytes(&query[i + 1..=i + 2])[0] as char);
+        decoded.extend_from_slice(&query.as_bytes()[i_prev..i]);
+        let hex = query.get(i + 1..=i + 2).ok_or(Error::MalformedQuery)?;
+        let byte = util::hex_to_bytes(hex).map_err(|_| Error::MalformedQuery)?;
+        decoded.push(byte[0]);
         i_prev = i + 3;
     }
-    decoded.push_str(&query[i_prev..]);
-    decoded
+    decoded.extend_from_slice(&query.as_bytes()[i_prev..]);
+    String::from_utf8(decoded).map_err(|_| Error::MalformedQuery)
 }
 
 fn find_repeated_blocks(message: &[u8], block_size: usize) -> usize {
@@ -0,0 +1,15 @@
+pub mod native;
+#[cfg(feature = "openssl")]
+pub mod openssl;
+pub mod toy;
+
+/* ======== BLOCK CIPHER ======== */
+// A keyed block cipher, encrypting or decrypting a single block in place
+// The modes in ecb, cbc and ctr are built on this so that any backend can be used
+pub trait BlockCipher {
+    fn block_size(&self) -> usize;
+
+    fn encrypt_block(&self, block: &mut [u8]);
+
+    fn decrypt_block(&self, block: &mut [u8]);
+}
@@ -0,0 +1,85 @@
+use super::BlockCipher;
+use crate::{
+    aes::key::{AesKey, KeySize},
+    error::Error,
+};
+use openssl::{
+    cipher::{Cipher, CipherRef},
+    cipher_ctx::CipherCtx,
+};
+
+/* ======== OPENSSL AES ======== */
+const BLOCK_SIZE: usize = 16;
+
+/// AES backed by OpenSSL, for cross-checking the native implementation.
+///
+/// ```
+/// use cryptopals::aes::block::{native::Aes, openssl::OpensslAes, BlockCipher};
+///
+/// let key = b"YELLOW SUBMARINE";
+/// let (native, openssl) = (Aes::new(key)?, OpensslAes::new(key)?);
+///
+/// let mut block = *b"0123456789abcdef";
+/// let mut expected = block;
+/// native.encrypt_block(&mut block);
+/// openssl.encrypt_block(&mut expected);
+/// assert_eq!(block, expected);
+/// # Ok::<(), cryptopals::Error>(())
+/// ```
+pub struct OpensslAes {
+    key: Vec<u8>,
+    cipher: &'static CipherRef,
+}
+
+impl OpensslAes {
+    pub fn new(key: &[u8]) -> Result<OpensslAes, Error> {
+        Ok(OpensslAes::from_key(&AesKey::new(key)?))
+    }
+
+    pub fn from_key(key: &AesKey) -> OpensslAes {
+        let cipher = match key.size() {
+            KeySize::Aes128 => Cipher::aes_128_ecb(),
+            KeySize::Aes192 => Cipher::aes_192_ecb(),
+            KeySize::Aes256 => Cipher::aes_256_ecb(),
+        };
+
+        OpensslAes {
+            key: key.as_bytes().to_vec(),
+            cipher,
+        }
+    }
+
+    fn crypt_block(&self, block: &mut [u8], encrypt: bool) {
+        assert_eq!(block.len(), BLOCK_SIZE);
+
+        let mut ctx = CipherCtx::new().unwrap();
+        if encrypt {
+            ctx.encrypt_init(Some(self.cipher), Some(&self.key), None)
+                .unwrap();
+        } else {
+            ctx.decrypt_init(Some(self.cipher), Some(&self.key), None)
+                .unwrap();
+        }
+        ctx.set_padding(false);
+
+        let mut output = vec![];
+        ctx.cipher_update_vec(block, &mut output).unwrap();
+        ctx.cipher_final_vec(&mut output).unwrap();
+
+        block.copy_from_slice(&output);
+    }
+}
+
+impl BlockCipher for OpensslAes {
+    fn block_size(&self) -> usize {
+        BLOCK_SIZE
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        self.crypt_block(block, true);
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        self.crypt_block(block, false);
+    }
+}
@@ -0,0 +1,91 @@
+use super::BlockCipher;
+use crate::error::Error;
+
+/* ======== TOY 64-BIT CIPHER ======== */
+const BLOCK_SIZE: usize = 8;
+const KEY_SIZE: usize = 16;
+const ROUNDS: usize = 16;
+
+/// A 16-round Feistel network on 8-byte blocks with a 128-bit key, standing in for DES, 3DES and
+/// Blowfish when checking that the modes and attacks don't assume 16-byte blocks.
+/// It is not secure, and not meant to be.
+///
+/// ```
+/// use cryptopals::aes::{block::{toy::Toy64, BlockCipher}, cbc, padding::Pkcs7};
+///
+/// let toy = Toy64::new(b"YELLOW SUBMARINE")?;
+/// let mut block = *b"8 bytes!";
+/// toy.encrypt_block(&mut block);
+/// assert_ne!(&block, b"8 bytes!");
+/// toy.decrypt_block(&mut block);
+/// assert_eq!(&block, b"8 bytes!");
+///
+/// // CBC works on it as on AES, with an 8-byte IV and padding to 8 bytes
+/// let iv = [0u8; 8];
+/// let ciphertext = cbc::encrypt_cbc(b"attack at dawn", &toy, &iv, &Pkcs7)?;
+/// assert_eq!(ciphertext.len(), 16);
+/// assert_eq!(cbc::decrypt_cbc(&ciphertext, &toy, &iv, &Pkcs7)?, b"attack at dawn");
+/// # Ok::<(), cryptopals::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct Toy64 {
+    round_keys: [u32; ROUNDS],
+}
+
+impl Toy64 {
+    pub fn new(key: &[u8]) -> Result<Toy64, Error> {
+        if key.len() != KEY_SIZE {
+            return Err(Error::InvalidKeyLength { len: key.len() });
+        }
+
+        let words: Vec<u32> = key
+            .chunks_exact(4)
+            .map(|word| u32::from_be_bytes(word.try_into().unwrap()))
+            .collect();
+        let round_keys =
+            std::array::from_fn(|i| words[i % 4] ^ (i as u32 + 1).wrapping_mul(0x9e3779b9));
+
+        Ok(Toy64 { round_keys })
+    }
+}
+
+// Needn't be invertible, as the Feistel structure takes care of that
+fn round(half: u32, key: u32) -> u32 {
+    let x = (half ^ key).wrapping_mul(0x85ebca6b);
+    x.rotate_left(13) ^ (x >> 16)
+}
+
+fn split(block: &[u8]) -> (u32, u32) {
+    assert_eq!(block.len(), BLOCK_SIZE);
+    (
+        u32::from_be_bytes(block[..4].try_into().unwrap()),
+        u32::from_be_bytes(block[4..].try_into().unwrap()),
+    )
+}
+
+fn join(block: &mut [u8], left: u32, right: u32) {
+    block[..4].copy_from_slice(&left.to_be_bytes());
+    block[4..].copy_from_slice(&right.to_be_bytes());
+}
+
+impl BlockCipher for Toy64 {
+    fn block_size(&self) -> usize {
+        BLOCK_SIZE
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        let (mut left, mut right) = split(block);
+        for &key in &self.round_keys {
+            (left, right) = (right, left ^ round(right, key));
+        }
+        join(block, left, right);
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        let (mut left, mut right) = split(block);
+        for &key in self.round_keys.iter().rev() {
+            (left, right) = (right ^ round(left, key), left);
+        }
+        join(block, left, right);
+    }
+}
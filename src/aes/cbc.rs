@@ -1,41 +1,128 @@
 pub mod bitflipping;
+pub mod key_as_iv;
 pub mod padding_oracle;
 
-use super::ecb;
-use crate::xor;
+use super::{
+    block::{native::Aes, BlockCipher},
+    ecb,
+    padding::{NoPadding, Padding, Pkcs7},
+};
+use crate::{error::Error, xor};
 
 /* ========== CBC ========== */
-pub fn decrypt_aes_cbc(bytes: &[u8], key: &[u8], iv: &[u8]) -> Result<Vec<u8>, String> {
-    let padded: Vec<u8> = bytes
-        .chunks(16)
-        .map(|block| ecb::decrypt_aes_ecb(block, key))
+pub fn decrypt_aes_cbc(bytes: &[u8], key: &[u8], iv: &[u8]) -> Result<Vec<u8>, Error> {
+    decrypt_cbc(bytes, &Aes::new(key)?, iv, &Pkcs7)
+}
+
+/// Accepts 128, 192 and 256-bit keys, and always pads with PKCS#7.
+///
+/// ```
+/// use cryptopals::{aes::{block::native::Aes, cbc, padding::NoPadding}, util};
+///
+/// // NIST SP 800-38A F.2, which has no padding
+/// let keys = [
+///     "2b7e151628aed2a6abf7158809cf4f3c",
+///     "8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b",
+///     "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4",
+/// ];
+/// let plaintext =
+///     util::hex_to_bytes("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51")?;
+/// let iv = util::hex_to_bytes("000102030405060708090a0b0c0d0e0f")?;
+/// let ciphertexts = [
+///     "7649abac8119b246cee98e9b12e9197d5086cb9b507219ee95db113a917678b2",
+///     "4f021db243bc633d7178183a9fa071e8b4d9ada9ad7dedf4e5e738763f69145a",
+///     "f58c4c04d6e5f1ba779eabfb5f7bfbd69cfc4e967edb808d679f777bc6702c7d",
+/// ];
+/// for (key, ciphertext) in keys.iter().zip(ciphertexts) {
+///     let key = util::hex_to_bytes(key)?;
+///     let aes = Aes::new(&key)?;
+///     let encrypted = cbc::encrypt_cbc(&plaintext, &aes, &iv, &NoPadding)?;
+///     assert_eq!(util::bytes_to_hex(&encrypted), ciphertext);
+///     assert_eq!(cbc::decrypt_cbc(&encrypted, &aes, &iv, &NoPadding)?, plaintext);
+///
+///     // The same, plus a block of padding
+///     let encrypted = cbc::encrypt_aes_cbc(&plaintext, &key, &iv)?;
+///     assert_eq!(util::bytes_to_hex(&encrypted[..32]), ciphertext);
+///     assert_eq!(cbc::decrypt_aes_cbc(&encrypted, &key, &iv)?, plaintext);
+/// }
+/// # Ok::<(), cryptopals::Error>(())
+/// ```
+pub fn encrypt_aes_cbc(bytes: &[u8], key: &[u8], iv: &[u8]) -> Result<Vec<u8>, Error> {
+    encrypt_cbc(bytes, &Aes::new(key)?, iv, &Pkcs7)
+}
+
+/// Works with any block cipher and padding scheme.
+///
+/// ```
+/// use cryptopals::aes::{block::native::Aes, cbc, padding::{Iso7816, Pkcs7}};
+///
+/// let aes = Aes::new(b"YELLOW SUBMARINE")?;
+/// let iv = [0u8; 16];
+/// let ciphertext = cbc::encrypt_cbc(b"attack at dawn", &aes, &iv, &Iso7816)?;
+/// assert_eq!(cbc::decrypt_cbc(&ciphertext, &aes, &iv, &Iso7816)?, b"attack at dawn");
+///
+/// // 0x80 0x00 isn't PKCS#7 padding
+/// assert!(cbc::decrypt_cbc(&ciphertext, &aes, &iv, &Pkcs7).is_err());
+/// # Ok::<(), cryptopals::Error>(())
+/// ```
+pub fn decrypt_cbc(
+    bytes: &[u8],
+    cipher: &impl BlockCipher,
+    iv: &[u8],
+    padding: &(impl Padding + ?Sized),
+) -> Result<Vec<u8>, Error> {
+    let block_size = cipher.block_size();
+    check_iv_length(iv, block_size)?;
+
+    let decrypted = ecb::decrypt_ecb(bytes, cipher, &NoPadding)?;
+    let padded: Vec<u8> = decrypted
+        .chunks(block_size)
         .enumerate()
         .flat_map(|(i, block)| {
             xor::xor(
-                &block,
+                block,
                 if i == 0 {
                     iv
                 } else {
-                    &bytes[(i - 1) * 16..i * 16]
+                    &bytes[(i - 1) * block_size..i * block_size]
                 },
             )
         })
         .collect();
 
-    Ok(super::strip_pkcs7(&padded)?)
+    padding.unpad(&padded, block_size)
 }
 
-pub fn encrypt_aes_cbc(bytes: &[u8], key: &[u8], iv: &[u8]) -> Vec<u8> {
-    let mut encrypted: Vec<Vec<u8>> = super::pad_pkcs7(bytes, 16)
-        .chunks(16)
-        .map(|block| block.to_vec())
-        .collect();
+pub fn encrypt_cbc(
+    bytes: &[u8],
+    cipher: &impl BlockCipher,
+    iv: &[u8],
+    padding: &(impl Padding + ?Sized),
+) -> Result<Vec<u8>, Error> {
+    let block_size = cipher.block_size();
+    check_iv_length(iv, block_size)?;
+
+    let mut encrypted = padding.pad(bytes, block_size)?;
+    if !encrypted.len().is_multiple_of(block_size) {
+        return Err(Error::InvalidPlaintextLength {
+            len: encrypted.len(),
+        });
+    }
+
     let mut prev_ciphertext = iv.to_vec();
-    for block in encrypted.iter_mut() {
-        *block = xor::xor(&block, &prev_ciphertext);
-        *block = ecb::encrypt_aes_ecb(&block, key);
-        prev_ciphertext = block.clone();
+    for block in encrypted.chunks_exact_mut(block_size) {
+        block.copy_from_slice(&xor::xor(block, &prev_ciphertext));
+        cipher.encrypt_block(block);
+        prev_ciphertext = block.to_vec();
     }
 
-    encrypted.concat()
+    Ok(encrypted)
+}
+
+fn check_iv_length(iv: &[u8], block_size: usize) -> Result<(), Error> {
+    if iv.len() == block_size {
+        Ok(())
+    } else {
+        Err(Error::InvalidIvLength { len: iv.len() })
+    }
 }
@@ -1,9 +1,9 @@
 use rand::{thread_rng, Rng};
 
-use crate::util;
+use crate::{aes, error::Error, util};
 
 /* ======== CBC BITFLIPPING ATTACK ======== */
-pub fn cbc_bitflipping_attack_admin() -> Vec<u8> {
+pub fn cbc_bitflipping_attack_admin() -> Result<Vec<u8>, Error> {
     let userdata: String = ["a"; 16].join("");
 
     // Encode data
@@ -12,37 +12,34 @@ pub fn cbc_bitflipping_attack_admin() -> Vec<u8> {
         &userdata,
         ";comment2=%20like%20a%20pound%20of%20bacon",
     );
-    println!("Original plaintext: {}", encoded);
     let encoded = util::ascii_to_bytes(&encoded);
 
     // Generate oracle functions
     let (encrypt, decrypt) = gen_aes_cbc_encrypt_decrypt_oracles();
 
-    let mut ciphertext = encrypt(&encoded);
-    println!("Original ciphertext: {}", util::bytes_to_hex(&ciphertext));
+    let mut ciphertext = encrypt(&encoded)?;
 
     // Target bytes must be <= 16 bytes
     let target_bytes = b";admin=true;a=";
     let target_block = 3;
 
-    // Flip bits to get admin=true
-    for (i, &target_byte) in target_bytes.iter().enumerate() {
-        let original_byte = encoded[target_block * 16 + i];
-        let diff_bits = original_byte ^ target_byte;
-        *ciphertext.get_mut((target_block - 1) * 16 + i).unwrap() ^= diff_bits;
-    }
+    // Flip bits in the previous block to get admin=true
+    let offset = target_block * 16;
+    aes::flip_known_plaintext(
+        &mut ciphertext,
+        aes::BitflipMode::Cbc,
+        offset,
+        &encoded[offset..offset + target_bytes.len()],
+        target_bytes,
+    )?;
 
-    let plaintext = decrypt(&ciphertext).unwrap();
-
-    println!("Modified ciphertext: {}", util::bytes_to_hex(&ciphertext));
-    println!("Is admin: {}", is_admin(&plaintext));
-
-    plaintext
+    decrypt(&ciphertext)
 }
 
+#[allow(clippy::type_complexity)]
 fn gen_aes_cbc_encrypt_decrypt_oracles() -> (
-    impl Fn(&[u8]) -> Vec<u8>,
-    impl Fn(&[u8]) -> Result<Vec<u8>, String>,
+    impl Fn(&[u8]) -> Result<Vec<u8>, Error>,
+    impl Fn(&[u8]) -> Result<Vec<u8>, Error>,
 ) {
     let mut rng = thread_rng();
     let key: [u8; 16] = rng.gen();
@@ -54,11 +51,11 @@ fn gen_aes_cbc_encrypt_decrypt_oracles() -> (
     )
 }
 
-fn is_admin(bytes: &[u8]) -> bool {
+pub fn is_admin(bytes: &[u8]) -> bool {
     util::bytes_to_ascii(bytes).contains(";admin=true;")
 }
 
-fn profile_for(prefix: &str, userdata: &str, suffix: &str) -> String {
+pub fn profile_for(prefix: &str, userdata: &str, suffix: &str) -> String {
     let mut profile = String::new();
     profile.push_str(prefix);
     profile.push_str(&super::super::encode_meta(userdata, &[';', '=']));
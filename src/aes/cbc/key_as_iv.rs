@@ -0,0 +1,117 @@
+use rand::{thread_rng, Rng};
+
+use crate::{aes::padding, error::Error, oracle::DecryptionOracle, xor};
+
+/* ======== CBC KEY AS IV ATTACK ======== */
+/// Sends C1, 0, C1 so that P'1 = D(C1) ^ IV and P'3 = D(C1), then IV = K = P'1 ^ P'3.
+/// The last two blocks are kept so the padding is still valid.
+///
+/// ```
+/// use cryptopals::{aes::{self, cbc::key_as_iv}, Error};
+///
+/// let (encrypt, decrypt) = key_as_iv::gen_aes_cbc_key_as_iv_oracles();
+/// let ciphertext = encrypt(&[b'a'; 48]);
+///
+/// let key = key_as_iv::recover_key_as_iv(&ciphertext, &decrypt)?;
+/// assert_eq!(aes::cbc::decrypt_aes_cbc(&ciphertext, &key, &key)?, [b'a'; 48]);
+///
+/// // The attack needs three whole blocks to work with
+/// assert_eq!(
+///     key_as_iv::recover_key_as_iv(&ciphertext[..32], &decrypt),
+///     Err(Error::InvalidCiphertextLength { len: 32 })
+/// );
+/// # Ok::<(), cryptopals::Error>(())
+/// ```
+pub fn recover_key_as_iv(
+    ciphertext: &[u8],
+    oracle: &impl DecryptionOracle,
+) -> Result<Vec<u8>, Error> {
+    recover_key_as_iv_with_block_size(ciphertext, 16, oracle)
+}
+
+/// For ciphers with blocks other than 16 bytes. Whatever the key, this recovers the IV.
+///
+/// ```
+/// use cryptopals::{
+///     aes::{block::toy::Toy64, cbc::{self, key_as_iv}, padding::Pkcs7},
+///     Error,
+/// };
+///
+/// let toy = Toy64::new(b"YELLOW SUBMARINE")?;
+/// let iv = *b"initvect";
+/// let oracle = |ciphertext: &[u8]| {
+///     let plaintext = cbc::decrypt_cbc(ciphertext, &toy, &iv, &Pkcs7)?;
+///     if plaintext.is_ascii() {
+///         Ok(plaintext)
+///     } else {
+///         Err(Error::HighAscii { plaintext })
+///     }
+/// };
+///
+/// let ciphertext = cbc::encrypt_cbc(&[b'a'; 24], &toy, &iv, &Pkcs7)?;
+/// assert_eq!(key_as_iv::recover_key_as_iv_with_block_size(&ciphertext, 8, &oracle)?, iv);
+/// # Ok::<(), cryptopals::Error>(())
+/// ```
+pub fn recover_key_as_iv_with_block_size(
+    ciphertext: &[u8],
+    block_size: usize,
+    oracle: &impl DecryptionOracle,
+) -> Result<Vec<u8>, Error> {
+    padding::check_block_size(block_size)?;
+    if ciphertext.len() < block_size * 3 || !ciphertext.len().is_multiple_of(block_size) {
+        return Err(Error::InvalidCiphertextLength {
+            len: ciphertext.len(),
+        });
+    }
+
+    let first_block = &ciphertext[..block_size];
+    let mut modified = Vec::with_capacity(block_size * 5);
+    modified.extend_from_slice(first_block);
+    modified.extend(vec![0; block_size]);
+    modified.extend_from_slice(first_block);
+    modified.extend_from_slice(&ciphertext[ciphertext.len() - block_size * 2..]);
+
+    // The plaintext is only leaked if it is rejected
+    let plaintext = match oracle.decrypt(&modified) {
+        Err(Error::HighAscii { plaintext }) => plaintext,
+        Err(err) => return Err(err),
+        Ok(_) => {
+            return Err(Error::OracleUnavailable {
+                reason: "the forged ciphertext was accepted, so nothing leaked".to_string(),
+            })
+        }
+    };
+
+    match (
+        plaintext.get(..block_size),
+        plaintext.get(block_size * 2..block_size * 3),
+    ) {
+        (Some(p1), Some(p3)) => Ok(xor::xor(p1, p3)),
+        _ => Err(Error::InvalidPlaintextLength {
+            len: plaintext.len(),
+        }),
+    }
+}
+
+// Both oracles use the key as the IV
+// The decrypt oracle rejects high-ASCII plaintexts, returning the plaintext in the error
+#[allow(clippy::type_complexity)]
+pub fn gen_aes_cbc_key_as_iv_oracles() -> (
+    impl Fn(&[u8]) -> Vec<u8>,
+    impl Fn(&[u8]) -> Result<Vec<u8>, Error>,
+) {
+    let key: [u8; 16] = thread_rng().gen();
+    let iv = key;
+
+    (
+        move |plaintext: &[u8]| super::encrypt_aes_cbc(plaintext, &key, &iv).unwrap(),
+        move |ciphertext: &[u8]| {
+            let plaintext = super::decrypt_aes_cbc(ciphertext, &key, &iv)?;
+            if plaintext.is_ascii() {
+                Ok(plaintext)
+            } else {
+                Err(Error::HighAscii { plaintext })
+            }
+        },
+    )
+}
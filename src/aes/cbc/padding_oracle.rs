@@ -1,56 +1,101 @@
+pub mod server;
+
+use std::{
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    thread,
+};
+
 use rand::{thread_rng, Rng};
 
+use crate::{
+    aes::{self, block::native::Aes, padding::Padding},
+    error::Error,
+    oracle::PaddingOracle,
+    xor,
+};
+
 /* ======== PADDING ORACLE ATTACK ======== */
+/// The block size is taken from the IV, so this works for any block cipher under CBC.
+///
+/// ```
+/// use cryptopals::{
+///     aes::{self, block::toy::Toy64, cbc::{self, padding_oracle}, padding::Pkcs7},
+///     Error,
+/// };
+///
+/// // A toy cipher with 8-byte blocks
+/// let toy = Toy64::new(b"YELLOW SUBMARINE")?;
+/// let iv = *b"initvect";
+/// let ciphertext = cbc::encrypt_cbc(b"Ice Ice Baby", &toy, &iv, &Pkcs7)?;
+/// let oracle = |ciphertext: &[u8]| cbc::decrypt_cbc(ciphertext, &toy, &iv, &Pkcs7).is_ok();
+///
+/// let recovered = padding_oracle::cbc_padding_oracle_attack(&ciphertext, &iv, &oracle)?;
+/// assert_eq!(aes::strip_pkcs7(&recovered)?, b"Ice Ice Baby");
+///
+/// let (recovered, _) = padding_oracle::cbc_padding_oracle_attack_parallel(&ciphertext, &iv, &oracle)?;
+/// assert_eq!(aes::strip_pkcs7(&recovered)?, b"Ice Ice Baby");
+///
+/// // Lengths that don't fit the IV's block size are errors
+/// assert_eq!(
+///     padding_oracle::cbc_padding_oracle_attack(&ciphertext[1..], &iv, &oracle),
+///     Err(Error::InvalidCiphertextLength { len: 15 })
+/// );
+/// assert_eq!(
+///     padding_oracle::cbc_padding_oracle_attack(&ciphertext, &[], &oracle),
+///     Err(Error::InvalidIvLength { len: 0 })
+/// );
+/// # Ok::<(), cryptopals::Error>(())
+/// ```
 pub fn cbc_padding_oracle_attack(
     ciphertext: &[u8],
     iv: &[u8],
-    oracle: impl Fn(&[u8]) -> bool,
-) -> Vec<u8> {
-    assert_eq!(ciphertext.len() % 16, 0);
+    oracle: &impl PaddingOracle,
+) -> Result<Vec<u8>, Error> {
+    let block_size = block_size_of(ciphertext, iv)?;
 
     let mut plaintext: Vec<u8> = Vec::with_capacity(ciphertext.len());
 
     let mut prev_block = iv;
-    for block in ciphertext.chunks_exact(16) {
+    for block in ciphertext.chunks_exact(block_size) {
         let mut xor_block = prev_block.to_vec();
-        assert_eq!(xor_block.len(), 16);
 
-        let mut inter_block: Vec<u8> = Vec::with_capacity(16);
-        let mut plaintext_block: Vec<u8> = Vec::with_capacity(16);
-        for i in (0..16).rev() {
+        let mut inter_block: Vec<u8> = Vec::with_capacity(block_size);
+        let mut plaintext_block: Vec<u8> = Vec::with_capacity(block_size);
+        for i in (0..block_size).rev() {
             // Find byte which results in valid padding
-            let mut b = u8::MIN;
-            while b <= u8::MAX {
+            let mut found = None;
+            for b in u8::MIN..=u8::MAX {
                 xor_block[i] = b;
-                let mut new_ciphertext = Vec::with_capacity(16 * 2);
+                let mut new_ciphertext = Vec::with_capacity(block_size * 2);
                 new_ciphertext.extend(&xor_block);
                 new_ciphertext.extend_from_slice(block);
-                let is_valid_padding = oracle(&new_ciphertext);
+                let is_valid_padding = oracle.check_padding(&new_ciphertext)?;
 
                 if is_valid_padding {
                     if i == 0 {
+                        found = Some(b);
                         break;
                     }
                     // Check if padding relies on prior bytes
                     // i.e. if this were the last byte, check if padding is only 0x01
                     new_ciphertext[i - 1] ^= 1;
-                    let is_valid_padding = oracle(&new_ciphertext);
+                    let is_valid_padding = oracle.check_padding(&new_ciphertext)?;
                     if is_valid_padding {
+                        found = Some(b);
                         break;
                     }
                 }
-
-                b += 1;
             }
+            let b = found.ok_or(Error::NoValidPadding)?;
 
             // Find actual plaintext from that byte
-            let inter_byte = b ^ (16 - i) as u8;
+            let inter_byte = b ^ (block_size - i) as u8;
             inter_block.push(inter_byte);
             let plaintext_byte = inter_byte ^ prev_block[i];
             plaintext_block.push(plaintext_byte);
 
-            for j in i..16 {
-                xor_block[j] = inter_block[16 - j - 1] ^ (16 - i + 1) as u8;
+            for j in i..block_size {
+                xor_block[j] = inter_block[block_size - j - 1] ^ (block_size - i + 1) as u8;
             }
         }
         plaintext_block.reverse();
@@ -59,7 +104,338 @@ pub fn cbc_padding_oracle_attack(
         prev_block = block;
     }
 
-    plaintext
+    Ok(plaintext)
+}
+
+/// Attacks the blocks in parallel, one thread per core, guessing the likeliest plaintext bytes
+/// first. Returns the padded plaintext and the number of oracle queries used.
+///
+/// ```
+/// use cryptopals::{aes::cbc::padding_oracle, Error};
+///
+/// let plaintext = b"Rollin' in my 5.0, with my rag-top down so my hair can blow";
+/// let (ciphertext, iv, oracle) = padding_oracle::gen_aes_cbc_padding_oracle(plaintext);
+///
+/// let (recovered, queries) =
+///     padding_oracle::cbc_padding_oracle_attack_parallel(&ciphertext, &iv, &oracle)?;
+/// assert_eq!(recovered, padding_oracle::cbc_padding_oracle_attack(&ciphertext, &iv, &oracle)?);
+///
+/// // English text takes far fewer than the 128 queries per byte of trying values in order
+/// assert!(queries < recovered.len() * 32);
+///
+/// // An oracle that never accepts anything is an error, not a panic
+/// let reject = |_: &[u8]| false;
+/// assert_eq!(
+///     padding_oracle::cbc_padding_oracle_attack_parallel(&ciphertext, &iv, &reject),
+///     Err(Error::NoValidPadding)
+/// );
+/// assert_eq!(
+///     padding_oracle::cbc_padding_oracle_attack(&ciphertext, &iv, &reject),
+///     Err(Error::NoValidPadding)
+/// );
+/// # Ok::<(), cryptopals::Error>(())
+/// ```
+pub fn cbc_padding_oracle_attack_parallel(
+    ciphertext: &[u8],
+    iv: &[u8],
+    oracle: &(impl PaddingOracle + Sync),
+) -> Result<(Vec<u8>, usize), Error> {
+    let block_size = block_size_of(ciphertext, iv)?;
+
+    let queries = AtomicUsize::new(0);
+    let query = |forged: &[u8]| {
+        queries.fetch_add(1, Ordering::Relaxed);
+        oracle.check_padding(forged)
+    };
+
+    // Each block only depends on the one before it, so the cores take blocks in turn
+    // Once one fails, the others stop at the end of their current block
+    let blocks: Vec<&[u8]> = ciphertext.chunks_exact(block_size).collect();
+    let threads = thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(blocks.len());
+    let next_block = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+
+    let recovered = thread::scope(|s| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                let (blocks, next_block, failed, query) = (&blocks, &next_block, &failed, &query);
+                s.spawn(move || {
+                    let mut recovered = Vec::new();
+                    while !failed.load(Ordering::Relaxed) {
+                        let i = next_block.fetch_add(1, Ordering::Relaxed);
+                        if i >= blocks.len() {
+                            break;
+                        }
+
+                        let prev_block = if i == 0 { iv } else { blocks[i - 1] };
+                        let is_last = i == blocks.len() - 1;
+                        match attack_block(prev_block, blocks[i], is_last, query) {
+                            Ok(plaintext) => recovered.push((i, plaintext)),
+                            Err(err) => {
+                                failed.store(true, Ordering::Relaxed);
+                                return Err(err);
+                            }
+                        }
+                    }
+                    Ok(recovered)
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .map(|worker| worker.join().unwrap())
+            .collect::<Result<Vec<_>, Error>>()
+    });
+
+    let mut recovered = recovered?.concat();
+    recovered.sort_by_key(|&(i, _)| i);
+    let plaintext = recovered.into_iter().flat_map(|(_, block)| block).collect();
+
+    Ok((plaintext, queries.into_inner()))
+}
+
+// The IV gives the block size, which the ciphertext has to be whole blocks of
+// Padding bytes count up to the block size, so it has to fit in a byte
+fn block_size_of(ciphertext: &[u8], iv: &[u8]) -> Result<usize, Error> {
+    if !(1..=u8::MAX as usize).contains(&iv.len()) {
+        return Err(Error::InvalidIvLength { len: iv.len() });
+    }
+    if !ciphertext.len().is_multiple_of(iv.len()) {
+        return Err(Error::InvalidCiphertextLength {
+            len: ciphertext.len(),
+        });
+    }
+
+    Ok(iv.len())
+}
+
+// Recovers one block's plaintext from the end, forging the block before it
+fn attack_block(
+    prev_block: &[u8],
+    block: &[u8],
+    is_last: bool,
+    query: impl Fn(&[u8]) -> Result<bool, Error>,
+) -> Result<Vec<u8>, Error> {
+    let intermediate = recover_intermediate(prev_block, block, is_last, query)?;
+    Ok(xor::xor(&intermediate, prev_block))
+}
+
+// Recovers the raw block cipher decryption of block, one byte at a time from the end
+// prev_block only steers the guesses, towards likely plaintext under it
+fn recover_intermediate(
+    prev_block: &[u8],
+    block: &[u8],
+    is_last: bool,
+    query: impl Fn(&[u8]) -> Result<bool, Error>,
+) -> Result<Vec<u8>, Error> {
+    let block_size = block.len();
+    let last = block_size - 1;
+    let mut intermediate = vec![0u8; block_size];
+
+    let mut forged = vec![0u8; block_size * 2];
+    forged[block_size..].copy_from_slice(block);
+
+    for i in (0..block_size).rev() {
+        let pad = (block_size - i) as u8;
+        for j in i + 1..block_size {
+            forged[j] = intermediate[j] ^ pad;
+        }
+
+        // The last block ends in padding, and once its length is known the rest of it is too
+        let hint = match (is_last, intermediate[last] ^ prev_block[last]) {
+            (true, _) if i == last => Some(1),
+            (true, n)
+                if (1..=block_size).contains(&(n as usize)) && i >= block_size - n as usize =>
+            {
+                Some(n)
+            }
+            _ => None,
+        };
+
+        let mut found = None;
+        for guess in guess_order(hint, block_size, is_last && i == last) {
+            forged[i] = prev_block[i] ^ guess ^ pad;
+            if !query(&forged)? {
+                continue;
+            }
+
+            // For the last byte, \x02\x02 and the like also pass, so change the byte before it
+            if i == last && i > 0 {
+                forged[i - 1] ^= 1;
+                let still_valid = query(&forged)?;
+                forged[i - 1] ^= 1;
+                if !still_valid {
+                    continue;
+                }
+            }
+            found = Some(guess);
+            break;
+        }
+        let byte = found.ok_or(Error::NoValidPadding)?;
+
+        intermediate[i] = prev_block[i] ^ byte;
+    }
+
+    Ok(intermediate)
+}
+
+/* ======== PADDING ORACLE ENCRYPTION ======== */
+/// Forges an IV and ciphertext that decrypt to any plaintext, without the key.
+/// Works backwards from a random last block: each block's intermediate state, recovered with the
+/// oracle, is XORed with the plaintext to give the block before it.
+/// Against a server with a fixed IV, send `iv || ciphertext`; only the first block decrypts to junk.
+///
+/// ```
+/// use cryptopals::aes::cbc::{self, padding_oracle};
+///
+/// // The server's key and IV, which the attack never sees
+/// let key = b"YELLOW SUBMARINE";
+/// let server_iv = [0u8; 16];
+/// let oracle = |ciphertext: &[u8]| cbc::decrypt_aes_cbc(ciphertext, key, &server_iv).is_ok();
+///
+/// let (iv, ciphertext) = padding_oracle::cbc_padding_oracle_encrypt(b"role=admin", &oracle)?;
+/// assert_eq!(cbc::decrypt_aes_cbc(&ciphertext, key, &iv)?, b"role=admin");
+///
+/// // Behind the server's own IV, the forged IV becomes a first block of junk
+/// let token = [iv, ciphertext].concat();
+/// let decrypted = cbc::decrypt_aes_cbc(&token, key, &server_iv)?;
+/// assert_eq!(&decrypted[16..], b"role=admin");
+/// # Ok::<(), cryptopals::Error>(())
+/// ```
+pub fn cbc_padding_oracle_encrypt(
+    plaintext: &[u8],
+    oracle: &impl PaddingOracle,
+) -> Result<(/* iv */ Vec<u8>, /* ciphertext */ Vec<u8>), Error> {
+    cbc_padding_oracle_encrypt_with_block_size(plaintext, 16, oracle)
+}
+
+/// For oracles in front of ciphers with blocks other than 16 bytes.
+///
+/// ```
+/// use cryptopals::aes::{block::toy::Toy64, cbc::{self, padding_oracle}, padding::Pkcs7};
+///
+/// let toy = Toy64::new(b"YELLOW SUBMARINE")?;
+/// let oracle = |ciphertext: &[u8]| {
+///     cbc::decrypt_cbc(&ciphertext[8..], &toy, &ciphertext[..8], &Pkcs7).is_ok()
+/// };
+///
+/// let (iv, ciphertext) =
+///     padding_oracle::cbc_padding_oracle_encrypt_with_block_size(b"role=admin", 8, &oracle)?;
+/// assert_eq!((iv.len(), ciphertext.len()), (8, 16));
+/// assert_eq!(cbc::decrypt_cbc(&ciphertext, &toy, &iv, &Pkcs7)?, b"role=admin");
+/// # Ok::<(), cryptopals::Error>(())
+/// ```
+pub fn cbc_padding_oracle_encrypt_with_block_size(
+    plaintext: &[u8],
+    block_size: usize,
+    oracle: &impl PaddingOracle,
+) -> Result<(/* iv */ Vec<u8>, /* ciphertext */ Vec<u8>), Error> {
+    let padded = aes::pad_pkcs7(plaintext, block_size)?;
+
+    let mut rng = thread_rng();
+    let mut block: Vec<u8> = (0..block_size).map(|_| rng.gen()).collect();
+    let mut blocks = vec![block.clone()];
+    for plaintext_block in padded.chunks_exact(block_size).rev() {
+        let intermediate = recover_intermediate(&vec![0; block_size], &block, false, |forged| {
+            oracle.check_padding(forged)
+        })?;
+        block = xor::xor(&intermediate, plaintext_block);
+        blocks.push(block.clone());
+    }
+    blocks.reverse();
+
+    let iv = blocks.remove(0);
+    Ok((iv, blocks.concat()))
+}
+
+/* ======== ISO/IEC 7816-4 PADDING ORACLE ATTACK ======== */
+/// The same attack against an oracle that checks ISO/IEC 7816-4 padding (0x80, then zeros).
+/// Each byte is found by forging it to 0x80 with zeros after it, instead of a run of PKCS#7 bytes.
+///
+/// ```
+/// use cryptopals::aes::{cbc::padding_oracle, padding::{Iso7816, Padding}};
+///
+/// let plaintext = b"Ice Ice Baby, too cold, too cold";
+/// let (ciphertext, iv, oracle) =
+///     padding_oracle::gen_aes_cbc_padding_oracle_with_padding(plaintext, Iso7816);
+///
+/// let recovered = padding_oracle::cbc_iso7816_padding_oracle_attack(&ciphertext, &iv, &oracle)?;
+/// assert_eq!(Iso7816.unpad(&recovered, 16)?, plaintext);
+/// # Ok::<(), cryptopals::Error>(())
+/// ```
+pub fn cbc_iso7816_padding_oracle_attack(
+    ciphertext: &[u8],
+    iv: &[u8],
+    oracle: &impl PaddingOracle,
+) -> Result<Vec<u8>, Error> {
+    let block_size = block_size_of(ciphertext, iv)?;
+
+    let mut plaintext = Vec::with_capacity(ciphertext.len());
+
+    let mut prev_block = iv;
+    for block in ciphertext.chunks_exact(block_size) {
+        let mut intermediate = vec![0u8; block_size];
+
+        let mut forged = vec![0u8; block_size * 2];
+        forged[block_size..].copy_from_slice(block);
+
+        for i in (0..block_size).rev() {
+            // Zeros after the byte being found
+            forged[i + 1..block_size].copy_from_slice(&intermediate[i + 1..]);
+
+            let mut found = None;
+            for b in 0..=u8::MAX {
+                forged[i] = b;
+                if !oracle.check_padding(&forged)? {
+                    continue;
+                }
+
+                // A zero here after an earlier 0x80 also passes, but not once the byte before changes
+                if i > 0 {
+                    forged[i - 1] ^= 1;
+                    let still_valid = oracle.check_padding(&forged)?;
+                    forged[i - 1] ^= 1;
+                    if !still_valid {
+                        continue;
+                    }
+                }
+                found = Some(b);
+                break;
+            }
+            let byte = found.ok_or(Error::NoValidPadding)?;
+
+            intermediate[i] = byte ^ 0x80;
+        }
+
+        plaintext.extend(xor::xor(&intermediate, prev_block));
+        prev_block = block;
+    }
+
+    Ok(plaintext)
+}
+
+// Every byte, roughly in order of how likely it is in English text
+fn guess_order(hint: Option<u8>, block_size: usize, padding_first: bool) -> Vec<u8> {
+    let padding: Vec<u8> = (1..=block_size as u8).collect();
+    let text = b" etaoinsrhldcumfpgwybvkxjqzETAOINSRHLDCUMFPGWYBVKXJQZ0123456789.,'\"!?-:;()\n";
+
+    let mut order: Vec<u8> = hint.into_iter().collect();
+    if padding_first {
+        order.extend(&padding);
+        order.extend(text);
+    } else {
+        order.extend(text);
+        order.extend(&padding);
+    }
+    order.extend(0..=u8::MAX);
+
+    // Keep only the first time each byte appears
+    let mut seen = [false; 256];
+    order.retain(|&b| !std::mem::replace(&mut seen[b as usize], true));
+    order
 }
 
 // The oracle function returns true or false depending on whether the padding is valid
@@ -77,7 +453,50 @@ pub fn gen_aes_cbc_padding_oracle(
 
     let oracle = move |ciphertext: &[u8]| super::decrypt_aes_cbc(ciphertext, &key, &iv).is_ok();
 
-    let ciphertext = super::encrypt_aes_cbc(plaintext, &key, &iv);
+    let ciphertext = super::encrypt_aes_cbc(plaintext, &key, &iv).unwrap();
+
+    (ciphertext, iv.to_vec(), oracle)
+}
+
+/// Same as [`gen_aes_cbc_padding_oracle`], with any padding scheme.
+/// Not every scheme leaks through the oracle: zero padding can't fail, and ISO 10126 only checks
+/// the final length byte.
+///
+/// ```
+/// use cryptopals::aes::{cbc::padding_oracle, padding::{Iso10126, ZeroPadding}};
+///
+/// let (mut ciphertext, _, oracle) =
+///     padding_oracle::gen_aes_cbc_padding_oracle_with_padding(b"YELLOW SUBMARINE", ZeroPadding);
+/// for byte in 0..=u8::MAX {
+///     ciphertext[0] = byte;
+///     assert!(oracle(&ciphertext));
+/// }
+///
+/// // The last block is all padding, so flipping bits in the block before only changes the filler
+/// let (mut ciphertext, _, oracle) =
+///     padding_oracle::gen_aes_cbc_padding_oracle_with_padding(b"YELLOW SUBMARINE", Iso10126);
+/// for i in 0..15 {
+///     ciphertext[i] ^= 1;
+///     assert!(oracle(&ciphertext));
+/// }
+/// ```
+pub fn gen_aes_cbc_padding_oracle_with_padding(
+    plaintext: &[u8],
+    padding: impl Padding,
+) -> (
+    /* ciphertext */ Vec<u8>,
+    /* iv */ Vec<u8>,
+    /* oracle fn */ impl Fn(&[u8]) -> bool,
+) {
+    let mut rng = thread_rng();
+
+    let aes = Aes::new(&rng.gen::<[u8; 16]>()).unwrap();
+    let iv: [u8; 16] = rng.gen();
+
+    let ciphertext = super::encrypt_cbc(plaintext, &aes, &iv, &padding).unwrap();
+
+    let oracle =
+        move |ciphertext: &[u8]| super::decrypt_cbc(ciphertext, &aes, &iv, &padding).is_ok();
 
     (ciphertext, iv.to_vec(), oracle)
 }
@@ -0,0 +1,112 @@
+use std::{
+    io,
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    thread,
+};
+
+use rand::{thread_rng, Rng};
+
+use crate::{aes, error::Error, http, util};
+
+/* ======== PADDING ORACLE SERVER ======== */
+// A token is hex(iv || AES-CBC(key, iv, secret)), and the server accepts it in hex or base64
+// GET /      issues a token for the secret, with a fresh IV, in a token cookie
+// GET /check decrypts the token cookie, or a token query parameter,
+//            answering 200 if the padding is valid, 500 if not, 400 if the token is malformed
+pub struct PaddingOracleServer {
+    listener: TcpListener,
+    key: [u8; 16],
+    secret: Vec<u8>,
+}
+
+impl PaddingOracleServer {
+    // Binds now, so the address is known before serving; the key is random
+    pub fn bind(addr: impl ToSocketAddrs, secret: &[u8]) -> io::Result<Self> {
+        Ok(PaddingOracleServer {
+            listener: TcpListener::bind(addr)?,
+            key: thread_rng().gen(),
+            secret: secret.to_vec(),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub fn token(&self) -> String {
+        issue_token(&self.key, &self.secret)
+    }
+
+    // Blocks, serving each connection on its own thread
+    pub fn run(self) {
+        for stream in self.listener.incoming().flatten() {
+            let secret = self.secret.clone();
+            thread::spawn(move || handle_request(stream, &self.key, &secret));
+        }
+    }
+
+    // Serves on a background thread until the process exits
+    pub fn spawn(self) -> io::Result<SocketAddr> {
+        let addr = self.local_addr()?;
+        thread::spawn(move || self.run());
+        Ok(addr)
+    }
+}
+
+fn issue_token(key: &[u8], secret: &[u8]) -> String {
+    let iv: [u8; 16] = thread_rng().gen();
+    let mut token = iv.to_vec();
+    token.extend(aes::cbc::encrypt_aes_cbc(secret, key, &iv).unwrap());
+    util::bytes_to_hex(&token)
+}
+
+fn handle_request(mut stream: TcpStream, key: &[u8], secret: &[u8]) -> io::Result<()> {
+    let request = match http::read_request(&stream) {
+        Ok(request) if request.method == "GET" => request,
+        _ => return http::write_response(&mut stream, 400, &[]),
+    };
+
+    match request.path() {
+        "/" => {
+            let cookie = format!("token={}", issue_token(key, secret));
+            http::write_response(&mut stream, 200, &[("Set-Cookie", &cookie)])
+        }
+        "/check" => {
+            let status = match check_token(&request, key) {
+                Some(true) => 200,
+                Some(false) => 500,
+                None => 400,
+            };
+            http::write_response(&mut stream, status, &[])
+        }
+        _ => http::write_response(&mut stream, 404, &[]),
+    }
+}
+
+// None if there is no token, or it can't be an IV followed by whole blocks
+fn check_token(request: &http::Request, key: &[u8]) -> Option<bool> {
+    let query = match request.query() {
+        Some(query) => aes::parse_query_string(query).ok()?,
+        None => Vec::new(),
+    };
+    let token = request.cookie("token").or_else(|| {
+        query
+            .iter()
+            .find(|(key, _)| key == "token")
+            .map(|(_, value)| value.as_str())
+    })?;
+
+    let token = util::hex_to_bytes(token)
+        .or_else(|_| util::base64_to_bytes(token))
+        .ok()?;
+    if token.len() < 32 || token.len() % 16 != 0 {
+        return None;
+    }
+
+    let (iv, ciphertext) = token.split_at(16);
+    match aes::cbc::decrypt_aes_cbc(ciphertext, key, iv) {
+        Ok(_) => Some(true),
+        Err(Error::BadPadding) => Some(false),
+        Err(_) => None,
+    }
+}
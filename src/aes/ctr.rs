@@ -1,44 +1,191 @@
+pub mod bitflipping;
 pub mod fixed_nonce;
+pub mod random_access;
 
 use std::{collections::VecDeque, iter::from_fn};
 
-use super::ecb;
-use crate::xor;
+use super::block::{native::Aes, BlockCipher};
+use crate::{error::Error, xor};
 
 /* ======== CTR ======== */
-pub fn decrypt_aes_ctr(ciphertext: &[u8], key: &[u8], nonce: &[u8]) -> Vec<u8> {
-    let keystream: Vec<u8> = ctr_keystream(key, nonce).take(ciphertext.len()).collect();
+pub fn decrypt_aes_ctr(ciphertext: &[u8], key: &[u8], nonce: &[u8]) -> Result<Vec<u8>, Error> {
+    decrypt_ctr(ciphertext, &Aes::new(key)?, nonce)
+}
+
+pub fn encrypt_aes_ctr(plaintext: &[u8], key: &[u8], nonce: &[u8]) -> Result<Vec<u8>, Error> {
+    encrypt_ctr(plaintext, &Aes::new(key)?, nonce)
+}
 
-    xor::xor(ciphertext, &keystream)
+// Decryption and encryption are the same operation in CTR mode
+pub fn decrypt_ctr(
+    ciphertext: &[u8],
+    cipher: &impl BlockCipher,
+    nonce: &[u8],
+) -> Result<Vec<u8>, Error> {
+    encrypt_ctr(ciphertext, cipher, nonce)
 }
 
-pub fn encrypt_aes_ctr(plaintext: &[u8], key: &[u8], nonce: &[u8]) -> Vec<u8> {
-    let keystream: Vec<u8> = ctr_keystream(key, nonce).take(plaintext.len()).collect();
+pub fn encrypt_ctr(
+    plaintext: &[u8],
+    cipher: &impl BlockCipher,
+    nonce: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let keystream: Vec<u8> = keystream_at(cipher, nonce, 0)?
+        .take(plaintext.len())
+        .collect();
 
-    xor::xor(plaintext, &keystream)
+    Ok(xor::xor(plaintext, &keystream))
 }
 
-fn ctr_keystream(key: &[u8], nonce: &[u8]) -> impl Iterator<Item = u8> {
-    let key = key.to_vec();
-    let nonce_len = nonce.len();
+// Counter blocks are the nonce followed by a 64-bit little-endian block count
+// Only the blocks from the offset onwards are generated
+pub fn keystream_at<'a>(
+    cipher: &'a impl BlockCipher,
+    nonce: &[u8],
+    offset: u64,
+) -> Result<impl Iterator<Item = u8> + 'a, Error> {
+    // The other half of each block is the 64-bit counter
+    if nonce.len() + 8 != cipher.block_size() {
+        return Err(Error::InvalidNonceLength { len: nonce.len() });
+    }
 
     let mut nonce_rev = nonce.to_vec();
     nonce_rev.reverse();
+    let counter_block = move |counter: u64| [nonce_rev.as_slice(), &counter.to_le_bytes()].concat();
+
+    Ok(ctr_keystream(cipher, counter_block, offset))
+}
+
+/* ======== RANDOM ACCESS ======== */
+/// Replaces the plaintext at `offset` with `newtext`, only re-encrypting the edited bytes.
+/// The ciphertext grows if the edit runs past its end.
+///
+/// ```
+/// use cryptopals::aes::ctr;
+///
+/// let (key, nonce) = (b"YELLOW SUBMARINE", [0; 8]);
+/// let mut ciphertext = ctr::encrypt_aes_ctr(b"attack at dawn", key, &nonce)?;
+///
+/// ctr::edit_aes_ctr(&mut ciphertext, key, &nonce, 10, b"dusk!")?;
+/// assert_eq!(ciphertext, ctr::encrypt_aes_ctr(b"attack at dusk!", key, &nonce)?);
+/// # Ok::<(), cryptopals::Error>(())
+/// ```
+pub fn edit_aes_ctr(
+    ciphertext: &mut Vec<u8>,
+    key: &[u8],
+    nonce: &[u8],
+    offset: usize,
+    newtext: &[u8],
+) -> Result<(), Error> {
+    edit_ctr(ciphertext, &Aes::new(key)?, nonce, offset, newtext)
+}
+
+pub fn edit_ctr(
+    ciphertext: &mut Vec<u8>,
+    cipher: &impl BlockCipher,
+    nonce: &[u8],
+    offset: usize,
+    newtext: &[u8],
+) -> Result<(), Error> {
+    if offset > ciphertext.len() {
+        return Err(Error::InvalidOffset { offset });
+    }
+
+    let keystream: Vec<u8> = keystream_at(cipher, nonce, offset as u64)?
+        .take(newtext.len())
+        .collect();
+
+    let end = offset + newtext.len();
+    if end > ciphertext.len() {
+        ciphertext.resize(end, 0);
+    }
+    ciphertext[offset..end].copy_from_slice(&xor::xor(newtext, &keystream));
+
+    Ok(())
+}
+
+/// CTR as specified in NIST SP 800-38A, where the whole block is a big-endian counter.
+///
+/// ```
+/// use cryptopals::{aes::{block::native::Aes, ctr}, util};
+///
+/// // NIST SP 800-38A F.5
+/// let keys = [
+///     "2b7e151628aed2a6abf7158809cf4f3c",
+///     "8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b",
+///     "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4",
+/// ];
+/// let plaintext =
+///     util::hex_to_bytes("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51")?;
+/// let initial_counter = util::hex_to_bytes("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff")?;
+/// let ciphertexts = [
+///     "874d6191b620e3261bef6864990db6ce9806f66b7970fdff8617187bb9fffdff",
+///     "1abc932417521ca24f2b0459fe7e6e0b090339ec0aa6faefd5ccc2c6f4ce8e94",
+///     "601ec313775789a5b7a7f504bbf3d228f443e3ca4d62b59aca84e990cacaf5c5",
+/// ];
+/// for (key, ciphertext) in keys.iter().zip(ciphertexts) {
+///     let aes = Aes::new(&util::hex_to_bytes(key)?)?;
+///     let encrypted = ctr::encrypt_ctr_nist(&plaintext, &aes, &initial_counter)?;
+///     assert_eq!(util::bytes_to_hex(&encrypted), ciphertext);
+/// }
+/// # Ok::<(), cryptopals::Error>(())
+/// ```
+pub fn encrypt_ctr_nist(
+    plaintext: &[u8],
+    cipher: &impl BlockCipher,
+    initial_counter: &[u8],
+) -> Result<Vec<u8>, Error> {
+    // The whole block is the counter, so this only works with 128-bit blocks
+    if cipher.block_size() != 16 || initial_counter.len() != 16 {
+        return Err(Error::InvalidNonceLength {
+            len: initial_counter.len(),
+        });
+    }
+
+    let initial_counter = u128::from_be_bytes(initial_counter.try_into().unwrap());
+    let counter_block = |counter: u64| {
+        initial_counter
+            .wrapping_add(counter.into())
+            .to_be_bytes()
+            .to_vec()
+    };
+
+    let keystream: Vec<u8> = ctr_keystream(cipher, counter_block, 0)
+        .take(plaintext.len())
+        .collect();
+
+    Ok(xor::xor(plaintext, &keystream))
+}
+
+pub fn decrypt_ctr_nist(
+    ciphertext: &[u8],
+    cipher: &impl BlockCipher,
+    initial_counter: &[u8],
+) -> Result<Vec<u8>, Error> {
+    encrypt_ctr_nist(ciphertext, cipher, initial_counter)
+}
 
-    let mut counter: u64 = 0;
-    let mut keystream: VecDeque<u8> = VecDeque::with_capacity(16);
+// Encrypts the counter block for each block index in turn, starting from the byte offset
+fn ctr_keystream<'a>(
+    cipher: &'a impl BlockCipher,
+    counter_block: impl Fn(u64) -> Vec<u8> + 'a,
+    offset: u64,
+) -> impl Iterator<Item = u8> + 'a {
+    let block_size = cipher.block_size() as u64;
+    let mut counter: u64 = offset / block_size;
+    let mut keystream: VecDeque<u8> = VecDeque::with_capacity(cipher.block_size());
 
     from_fn(move || {
         if let Some(next) = keystream.pop_front() {
             Some(next)
         } else {
-            nonce_rev.extend_from_slice(&counter.to_le_bytes());
-            assert_eq!(nonce_rev.len(), 16);
-            keystream.extend(ecb::encrypt_aes_ecb(&nonce_rev, &key));
-            nonce_rev.truncate(nonce_len);
+            let mut block = counter_block(counter);
+            cipher.encrypt_block(&mut block);
+            keystream.extend(block);
             counter += 1;
 
             keystream.pop_front()
         }
     })
+    .skip((offset % block_size) as usize)
 }
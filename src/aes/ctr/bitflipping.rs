@@ -0,0 +1,64 @@
+use rand::{thread_rng, Rng};
+
+use crate::{
+    aes::{self, cbc::bitflipping::profile_for},
+    error::Error,
+    util,
+};
+
+/* ======== CTR BITFLIPPING ATTACK ======== */
+/// Flips `;admin=true;` into the userdata and returns what the server decrypts.
+///
+/// ```
+/// use cryptopals::aes::{cbc::bitflipping::is_admin, ctr::bitflipping};
+///
+/// assert!(is_admin(&bitflipping::ctr_bitflipping_attack_admin()?));
+/// # Ok::<(), cryptopals::Error>(())
+/// ```
+pub fn ctr_bitflipping_attack_admin() -> Result<Vec<u8>, Error> {
+    let prefix = "comment1=cooking%20MCs;userdata=";
+    let target_bytes = b";admin=true;a=";
+
+    // No spare block is needed, the userdata itself is flipped
+    let userdata: String = vec!["a"; target_bytes.len()].join("");
+
+    // Encode data
+    let encoded = profile_for(
+        prefix,
+        &userdata,
+        ";comment2=%20like%20a%20pound%20of%20bacon",
+    );
+    let encoded = util::ascii_to_bytes(&encoded);
+
+    // Generate oracle functions
+    let (encrypt, decrypt) = gen_aes_ctr_encrypt_decrypt_oracles();
+
+    let mut ciphertext = encrypt(&encoded)?;
+
+    // Flip bits in place to get admin=true
+    let offset = prefix.len();
+    aes::flip_known_plaintext(
+        &mut ciphertext,
+        aes::BitflipMode::Ctr,
+        offset,
+        &encoded[offset..offset + target_bytes.len()],
+        target_bytes,
+    )?;
+
+    decrypt(&ciphertext)
+}
+
+#[allow(clippy::type_complexity)]
+fn gen_aes_ctr_encrypt_decrypt_oracles() -> (
+    impl Fn(&[u8]) -> Result<Vec<u8>, Error>,
+    impl Fn(&[u8]) -> Result<Vec<u8>, Error>,
+) {
+    let mut rng = thread_rng();
+    let key: [u8; 16] = rng.gen();
+    let nonce: [u8; 8] = rng.gen();
+
+    (
+        move |plaintext: &[u8]| super::encrypt_aes_ctr(plaintext, &key, &nonce),
+        move |ciphertext: &[u8]| super::decrypt_aes_ctr(ciphertext, &key, &nonce),
+    )
+}
@@ -1,20 +1,27 @@
-use crate::{aes, util, xor};
+use crate::{aes, error::Error, util, xor};
 use rand::{thread_rng, Rng};
 
-pub fn decrypt_fixed_nonce_ctr_statistically(ciphertexts: &[Vec<u8>]) -> Vec<Vec<u8>> {
-    let min_len = ciphertexts.iter().map(|bytes| bytes.len()).min().unwrap();
+pub fn decrypt_fixed_nonce_ctr_statistically(
+    ciphertexts: &[Vec<u8>],
+) -> Result<Vec<Vec<u8>>, Error> {
+    // Every ciphertext is cut to the shortest, so one empty ciphertext leaves nothing
+    let min_len = match ciphertexts.iter().map(|bytes| bytes.len()).min() {
+        None | Some(0) => return Err(Error::EmptyInput),
+        Some(min_len) => min_len,
+    };
 
     let concat_ciphertext: Vec<u8> = ciphertexts
         .iter()
-        .flat_map(|bytes| (&bytes[..min_len]).to_vec())
+        .flat_map(|bytes| bytes[..min_len].to_vec())
         .collect();
 
-    let res = xor::decrypt_repeating_key_xor(&concat_ciphertext, min_len);
+    let res = xor::decrypt_repeating_key_xor(&concat_ciphertext, min_len)?;
 
-    res.message
+    Ok(res
+        .message
         .chunks(min_len)
         .map(|bytes| bytes.to_vec())
-        .collect()
+        .collect())
 }
 
 // The plaintext guessed is hardcoded for this specific challenge
@@ -62,7 +69,7 @@ QSB0ZXJyaWJsZSBiZWF1dHkgaXMgYm9ybi4=";
 
     let plaintexts: Vec<Vec<u8>> = plaintext
         .split_terminator('\n')
-        .map(|str| util::base64_to_bytes(str))
+        .map(|str| util::base64_to_bytes(str).unwrap())
         .collect();
 
     let nonce = 0u64.to_le_bytes();
@@ -72,7 +79,7 @@ QSB0ZXJyaWJsZSBiZWF1dHkgaXMgYm9ybi4=";
         for b in ciphertext {
             print!("{:>4}", b);
         }
-        print!("{}", '\n');
+        println!();
     }
 
     println!();
@@ -104,6 +111,6 @@ pub fn encrypt_fixed_nonce_ctr(plaintexts: &[Vec<u8>], nonce: &[u8]) -> Vec<Vec<
 
     plaintexts
         .iter()
-        .map(|bytes| aes::ctr::encrypt_aes_ctr(bytes, &key, nonce))
+        .map(|bytes| aes::ctr::encrypt_aes_ctr(bytes, &key, nonce).unwrap())
         .collect()
 }
@@ -0,0 +1,40 @@
+use crate::{
+    aes::{
+        ctr,
+        key::{AesKey, KeySize},
+    },
+    error::Error,
+};
+use rand::{thread_rng, Rng};
+
+/* ======== RANDOM ACCESS READ/WRITE ATTACK ======== */
+// Editing the ciphertext with itself as the new text XORs the keystream out,
+// leaving the plaintext
+pub fn recover_ctr_plaintext_with_edit(
+    ciphertext: &[u8],
+    edit: impl Fn(&[u8], usize, &[u8]) -> Result<Vec<u8>, Error>,
+) -> Result<Vec<u8>, Error> {
+    edit(ciphertext, 0, ciphertext)
+}
+
+// The edit oracle takes a ciphertext, offset and new text and returns the edited ciphertext
+#[allow(clippy::type_complexity)]
+pub fn gen_aes_ctr_edit_oracle(
+    plaintext: &[u8],
+) -> (
+    /* ciphertext */ Vec<u8>,
+    /* edit fn */ impl Fn(&[u8], usize, &[u8]) -> Result<Vec<u8>, Error>,
+) {
+    let key = AesKey::random(KeySize::Aes128);
+    let nonce: [u8; 8] = thread_rng().gen();
+
+    let ciphertext = ctr::encrypt_aes_ctr(plaintext, key.as_bytes(), &nonce).unwrap();
+
+    let edit = move |ciphertext: &[u8], offset: usize, newtext: &[u8]| {
+        let mut edited = ciphertext.to_vec();
+        ctr::edit_aes_ctr(&mut edited, key.as_bytes(), &nonce, offset, newtext)?;
+        Ok(edited)
+    };
+
+    (ciphertext, edit)
+}
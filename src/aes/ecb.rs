@@ -1,86 +1,122 @@
 pub mod cut_and_paste;
 
-use crate::util;
-use openssl::{cipher::Cipher, cipher_ctx::CipherCtx};
+use super::{
+    block::{native::Aes, BlockCipher},
+    padding::{Padding, Pkcs7},
+};
+use crate::{error::Error, oracle::EncryptionOracle};
 use rand::{thread_rng, Rng};
 
 /* ========== ECB ========== */
-pub fn decrypt_aes_ecb_with_oracle(oracle: impl Fn(&[u8]) -> Vec<u8>) -> Vec<u8> {
-    let encrypted = oracle(&[]);
-
-    // Find block offset
-    let corrupted = oracle(&[0]);
-
-    let offset = encrypted
-        .iter()
-        .zip(corrupted.iter())
-        .enumerate()
-        .find(|(_, (b1, b2))| b1 != b2)
-        .unwrap()
-        .0;
-
-    // Find block size of cipher (should be 16)
-    let mut block_size = 0;
-    for i in 1..(encrypted.len() - offset) {
-        if &oracle(&vec![0; i])[offset + i * 2..offset + i * 3]
-            == &encrypted[offset + i..offset + i * 2]
-        {
-            block_size = i;
+const MAX_BLOCK_SIZE: usize = 256;
+
+/// Recovers the secret an ECB oracle appends to its input, even behind a fixed prefix of unknown
+/// length. Anything that doesn't behave like that is [`Error::NotEcb`] rather than a panic.
+///
+/// ```
+/// use cryptopals::{aes::ecb, Error};
+///
+/// for secret in [&b"!"[..], b"YELLOW SUBMARINE", b"Rollin' in my 5.0"] {
+///     let oracle = ecb::gen_aes_ecb_oracle_padded(secret);
+///     assert_eq!(ecb::decrypt_aes_ecb_with_oracle(&oracle)?, secret);
+/// }
+///
+/// let not_ecb = |input: &[u8]| input.to_vec();
+/// assert_eq!(ecb::decrypt_aes_ecb_with_oracle(&not_ecb), Err(Error::NotEcb));
+/// # Ok::<(), cryptopals::Error>(())
+/// ```
+pub fn decrypt_aes_ecb_with_oracle(oracle: &impl EncryptionOracle) -> Result<Vec<u8>, Error> {
+    let encrypted = oracle.encrypt(&[])?;
+
+    // Find block size of cipher, from the jump in ciphertext length as the input grows
+    // Padding is never empty, so the jump comes within one block of input
+    let mut block_size = None;
+    for i in 1..=MAX_BLOCK_SIZE {
+        let len = oracle.encrypt(&vec![0; i])?.len();
+        if len > encrypted.len() {
+            block_size = Some(len - encrypted.len());
             break;
         }
     }
-    assert_eq!(block_size, 16);
+    let block_size = block_size.ok_or(Error::NotEcb)?;
 
-    assert_eq!(offset % block_size, 0);
-    let offset_block = offset / block_size;
+    // Find block offset, a whole block at a time, as single bytes may match by chance
+    let corrupted = oracle.encrypt(&[0])?;
+
+    let offset_block = encrypted
+        .chunks(block_size)
+        .zip(corrupted.chunks(block_size))
+        .position(|(b1, b2)| b1 != b2)
+        .ok_or(Error::NotEcb)?;
+    let offset = offset_block * block_size;
 
     // Find partial offset (block_size - extra prefix bytes in last prefix block)
     // This also checks if the function is using ECB
-    let offset_partial = (1..=block_size).find(|i| {
-        let encrypted = oracle(&vec![0; i + block_size * 2]);
-        &encrypted[offset + block_size..offset + block_size * 2]
-            == &encrypted[offset + block_size * 2..offset + block_size * 3]
-    });
-
-    assert_ne!(offset_partial, None);
-    let offset_partial = offset_partial.unwrap();
+    let mut offset_partial = None;
+    for i in 1..=block_size {
+        let encrypted = oracle.encrypt(&vec![0; i + block_size * 2])?;
+        if encrypted.len() >= offset + block_size * 3
+            && encrypted[offset + block_size..offset + block_size * 2]
+                == encrypted[offset + block_size * 2..offset + block_size * 3]
+        {
+            offset_partial = Some(i);
+            break;
+        }
+    }
+    let offset_partial = offset_partial.ok_or(Error::NotEcb)?;
 
     let mut unknown_string = Vec::with_capacity(encrypted.len());
 
     // Decrypt unknown string
-    let aligned = oracle(&vec![0; offset_partial]);
-    for block in offset_block + 1..aligned.len() / block_size {
+    let aligned = oracle.encrypt(&vec![0; offset_partial])?;
+    'blocks: for block in offset_block + 1..aligned.len() / block_size {
+        let range = block_size * block..block_size * (block + 1);
         let mut input = vec![0; offset_partial + block_size];
         while input.len() > offset_partial {
             // Make input block that is 1 byte short
             input.pop();
 
-            let encrypted = oracle(&input);
+            let encrypted = oracle.encrypt(&input)?;
+            let target = encrypted.get(range.clone()).ok_or(Error::NotEcb)?;
 
             // Match output
+            let mut found = false;
             for byte in u8::MIN..=u8::MAX {
                 let mut input = input.clone();
                 input.extend_from_slice(&unknown_string);
                 input.push(byte);
-                let matcher = oracle(&input);
+                let matcher = oracle.encrypt(&input)?;
 
-                if &encrypted[block_size * block..block_size * (block + 1)]
-                    == &matcher[block_size * block..block_size * (block + 1)]
-                {
+                if matcher.get(range.clone()) == Some(target) {
                     unknown_string.push(byte);
+                    found = true;
                     break;
                 }
             }
+
+            // Past the end of the secret the padding changes under us, so nothing matches
+            if !found {
+                break 'blocks;
+            }
         }
     }
 
-    unknown_string
+    // The last byte matched is the first byte of padding, a \x01
+    if unknown_string.pop() != Some(1) {
+        return Err(Error::NotEcb);
+    }
+    Ok(unknown_string)
 }
 
 pub fn gen_aes_ecb_oracle_padded(unknown_string: &[u8]) -> impl Fn(&[u8]) -> Vec<u8> {
-    let unknown_string = unknown_string.to_vec();
+    gen_aes_ecb_oracle_padded_with_rng(unknown_string, &mut thread_rng())
+}
 
-    let mut rng = thread_rng();
+pub fn gen_aes_ecb_oracle_padded_with_rng(
+    unknown_string: &[u8],
+    rng: &mut impl Rng,
+) -> impl Fn(&[u8]) -> Vec<u8> {
+    let unknown_string = unknown_string.to_vec();
 
     let mut key = [0u8; 16];
     rng.fill(&mut key);
@@ -95,9 +131,27 @@ pub fn gen_aes_ecb_oracle_padded(unknown_string: &[u8]) -> impl Fn(&[u8]) -> Vec
 }
 
 pub fn gen_aes_ecb_oracle(unknown_string: &[u8]) -> impl Fn(&[u8]) -> Vec<u8> {
+    gen_aes_ecb_oracle_with_rng(unknown_string, &mut thread_rng())
+}
+
+/// Same as the oracle, but the key comes from the given RNG.
+///
+/// ```
+/// use cryptopals::{aes::ecb, mt19937::MT19937};
+/// use rand::SeedableRng;
+///
+/// // The same seed gives the same key, so the ciphertexts match
+/// let oracle1 = ecb::gen_aes_ecb_oracle_with_rng(b"secret", &mut MT19937::seed_from_u64(42));
+/// let oracle2 = ecb::gen_aes_ecb_oracle_with_rng(b"secret", &mut MT19937::seed_from_u64(42));
+/// assert_eq!(oracle1(b"input"), oracle2(b"input"));
+/// ```
+pub fn gen_aes_ecb_oracle_with_rng(
+    unknown_string: &[u8],
+    rng: &mut impl Rng,
+) -> impl Fn(&[u8]) -> Vec<u8> {
     let unknown_string = unknown_string.to_vec();
     let mut key = [0u8; 16];
-    thread_rng().fill(&mut key);
+    rng.fill(&mut key);
 
     move |plaintext: &[u8]| aes_ecb_oracle(plaintext, &unknown_string, &key)
 }
@@ -107,52 +161,102 @@ fn aes_ecb_oracle(plaintext: &[u8], unknown_string: &[u8], key: &[u8]) -> Vec<u8
     let mut bytes = plaintext.to_vec();
     bytes.extend_from_slice(unknown_string);
 
-    encrypt_aes_ecb(&bytes, &key)
+    encrypt_aes_ecb(&bytes, key).unwrap()
 }
 
-pub fn detect_aes_ecb(messages: &Vec<Vec<u8>>) -> Vec<u8> {
+pub fn detect_aes_ecb(messages: &[Vec<u8>]) -> Result<Vec<u8>, Error> {
     let mut messages_count: Vec<(&Vec<u8>, usize)> = messages
         .iter()
         .map(|msg| (msg, super::find_repeated_blocks(msg, 16)))
         .collect();
     messages_count.sort_by(|(_, c1), (_, c2)| c2.cmp(c1));
 
-    for (message, count) in messages_count.iter() {
-        println!("{}: {}", util::bytes_to_hex(message), count);
-    }
+    messages_count
+        .first()
+        .map(|(msg, _)| msg.to_vec())
+        .ok_or(Error::EmptyInput)
+}
 
-    messages_count[0].0.to_vec()
+/// Accepts 128, 192 and 256-bit keys, and always pads with PKCS#7.
+///
+/// ```
+/// use cryptopals::{aes::{block::native::Aes, ecb, padding::NoPadding}, util};
+///
+/// // NIST SP 800-38A F.1, which has no padding
+/// let keys = [
+///     "2b7e151628aed2a6abf7158809cf4f3c",
+///     "8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b",
+///     "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4",
+/// ];
+/// let plaintext =
+///     util::hex_to_bytes("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51")?;
+/// let ciphertexts = [
+///     "3ad77bb40d7a3660a89ecaf32466ef97f5d3d58503b9699de785895a96fdbaaf",
+///     "bd334f1d6e45f25ff712a214571fa5cc974104846d0ad3ad7734ecb3ecee4eef",
+///     "f3eed1bdb5d2a03c064b5a7e3db181f8591ccb10d410ed26dc5ba74a31362870",
+/// ];
+/// for (key, ciphertext) in keys.iter().zip(ciphertexts) {
+///     let key = util::hex_to_bytes(key)?;
+///     let aes = Aes::new(&key)?;
+///     let encrypted = ecb::encrypt_ecb(&plaintext, &aes, &NoPadding)?;
+///     assert_eq!(util::bytes_to_hex(&encrypted), ciphertext);
+///     assert_eq!(ecb::decrypt_ecb(&encrypted, &aes, &NoPadding)?, plaintext);
+///
+///     // Whole blocks still get a block of padding, so it can always be removed
+///     let encrypted = ecb::encrypt_aes_ecb(&plaintext, &key)?;
+///     assert_eq!(encrypted.len(), 48);
+///     assert_eq!(util::bytes_to_hex(&encrypted[..32]), ciphertext);
+///     assert_eq!(ecb::decrypt_aes_ecb(&encrypted, &key)?, plaintext);
+/// }
+///
+/// assert!(ecb::encrypt_aes_ecb(&plaintext, &[0; 20]).is_err());
+/// let aes = Aes::new(&[0; 16])?;
+/// assert!(ecb::encrypt_ecb(&plaintext[..20], &aes, &NoPadding).is_err());
+/// # Ok::<(), cryptopals::Error>(())
+/// ```
+pub fn encrypt_aes_ecb(bytes: &[u8], key: &[u8]) -> Result<Vec<u8>, Error> {
+    encrypt_ecb(bytes, &Aes::new(key)?, &Pkcs7)
 }
 
-pub fn encrypt_aes_ecb(bytes: &[u8], key: &[u8]) -> Vec<u8> {
-    let cipher = Cipher::aes_128_ecb();
-    let mut ctx = CipherCtx::new().unwrap();
-    ctx.encrypt_init(Some(cipher), Some(key), None).unwrap();
-    ctx.set_padding(false);
+pub fn decrypt_aes_ecb(encrypted: &[u8], key: &[u8]) -> Result<Vec<u8>, Error> {
+    decrypt_ecb(encrypted, &Aes::new(key)?, &Pkcs7)
+}
+
+pub fn encrypt_ecb(
+    bytes: &[u8],
+    cipher: &impl BlockCipher,
+    padding: &(impl Padding + ?Sized),
+) -> Result<Vec<u8>, Error> {
+    let block_size = cipher.block_size();
 
-    // Manually pad bytes only if not block size
-    let bytes = if bytes.len() % 16 != 0 {
-        super::pad_pkcs7(bytes, 16)
-    } else {
-        bytes.to_vec()
-    };
+    let mut bytes = padding.pad(bytes, block_size)?;
+    if !bytes.len().is_multiple_of(block_size) {
+        return Err(Error::InvalidPlaintextLength { len: bytes.len() });
+    }
 
-    let mut encrypted = vec![];
-    ctx.cipher_update_vec(&bytes, &mut encrypted).unwrap();
-    ctx.cipher_final_vec(&mut encrypted).unwrap();
+    for block in bytes.chunks_exact_mut(block_size) {
+        cipher.encrypt_block(block);
+    }
 
-    encrypted
+    Ok(bytes)
 }
 
-pub fn decrypt_aes_ecb(encrypted: &[u8], key: &[u8]) -> Vec<u8> {
-    let cipher = Cipher::aes_128_ecb();
-    let mut ctx = CipherCtx::new().unwrap();
-    ctx.decrypt_init(Some(cipher), Some(key), None).unwrap();
-    ctx.set_padding(false);
+pub fn decrypt_ecb(
+    encrypted: &[u8],
+    cipher: &impl BlockCipher,
+    padding: &(impl Padding + ?Sized),
+) -> Result<Vec<u8>, Error> {
+    let block_size = cipher.block_size();
+    if !encrypted.len().is_multiple_of(block_size) {
+        return Err(Error::InvalidCiphertextLength {
+            len: encrypted.len(),
+        });
+    }
 
-    let mut decrypted = vec![];
-    ctx.cipher_update_vec(encrypted, &mut decrypted).unwrap();
-    ctx.cipher_final_vec(&mut decrypted).unwrap();
+    let mut decrypted = encrypted.to_vec();
+    for block in decrypted.chunks_exact_mut(block_size) {
+        cipher.decrypt_block(block);
+    }
 
-    decrypted
+    padding.unpad(&decrypted, block_size)
 }
@@ -1,4 +1,4 @@
-use crate::util;
+use crate::{error::Error, util};
 use rand::{thread_rng, Rng};
 
 /* ======== ECB CUT-AND-PASTE ATTACK ======== */
@@ -40,18 +40,19 @@ pub fn ecb_cut_and_paste_admin_profile() -> Vec<u8> {
         util::bytes_to_hex(&ciphertext_combined)
     );
 
-    let plaintext = decrypt(&ciphertext_combined);
-
-    plaintext
+    decrypt(&ciphertext_combined).unwrap()
 }
 
-pub fn gen_aes_ecb_encrypt_decrypt_oracles(
-) -> (impl Fn(&[u8]) -> Vec<u8>, impl Fn(&[u8]) -> Vec<u8>) {
+#[allow(clippy::type_complexity)]
+pub fn gen_aes_ecb_encrypt_decrypt_oracles() -> (
+    impl Fn(&[u8]) -> Vec<u8>,
+    impl Fn(&[u8]) -> Result<Vec<u8>, Error>,
+) {
     let mut key = [0u8; 16];
     thread_rng().fill(&mut key);
 
     (
-        move |plaintext: &[u8]| super::encrypt_aes_ecb(plaintext, &key),
+        move |plaintext: &[u8]| super::encrypt_aes_ecb(plaintext, &key).unwrap(),
         move |ciphertext: &[u8]| super::decrypt_aes_ecb(ciphertext, &key),
     )
 }
@@ -0,0 +1,89 @@
+use crate::error::Error;
+use rand::{thread_rng, Rng};
+
+/* ======== AES KEYS ======== */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeySize {
+    Aes128,
+    Aes192,
+    Aes256,
+}
+
+impl KeySize {
+    pub const ALL: [KeySize; 3] = [KeySize::Aes128, KeySize::Aes192, KeySize::Aes256];
+
+    pub fn key_len(self) -> usize {
+        match self {
+            KeySize::Aes128 => 16,
+            KeySize::Aes192 => 24,
+            KeySize::Aes256 => 32,
+        }
+    }
+
+    pub fn rounds(self) -> usize {
+        self.key_len() / 4 + 6
+    }
+}
+
+// A key whose length is checked once, so the modes never see an invalid key
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AesKey {
+    Aes128([u8; 16]),
+    Aes192([u8; 24]),
+    Aes256([u8; 32]),
+}
+
+impl AesKey {
+    pub fn new(key: &[u8]) -> Result<AesKey, Error> {
+        let invalid = || Error::InvalidKeyLength { len: key.len() };
+
+        match key.len() {
+            16 => Ok(AesKey::Aes128(key.try_into().map_err(|_| invalid())?)),
+            24 => Ok(AesKey::Aes192(key.try_into().map_err(|_| invalid())?)),
+            32 => Ok(AesKey::Aes256(key.try_into().map_err(|_| invalid())?)),
+            _ => Err(invalid()),
+        }
+    }
+
+    pub fn random(size: KeySize) -> AesKey {
+        AesKey::random_with_rng(size, &mut thread_rng())
+    }
+
+    pub fn random_with_rng(size: KeySize, rng: &mut impl Rng) -> AesKey {
+        match size {
+            KeySize::Aes128 => AesKey::Aes128(rng.gen()),
+            KeySize::Aes192 => AesKey::Aes192(rng.gen()),
+            KeySize::Aes256 => AesKey::Aes256(rng.gen()),
+        }
+    }
+
+    pub fn size(&self) -> KeySize {
+        match self {
+            AesKey::Aes128(_) => KeySize::Aes128,
+            AesKey::Aes192(_) => KeySize::Aes192,
+            AesKey::Aes256(_) => KeySize::Aes256,
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            AesKey::Aes128(key) => key,
+            AesKey::Aes192(key) => key,
+            AesKey::Aes256(key) => key,
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for AesKey {
+    type Error = Error;
+
+    fn try_from(key: &[u8]) -> Result<AesKey, Error> {
+        AesKey::new(key)
+    }
+}
+
+impl AsRef<[u8]> for AesKey {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
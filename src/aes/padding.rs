@@ -0,0 +1,245 @@
+use rand::{thread_rng, Rng};
+
+use crate::error::Error;
+
+/* ======== PADDING SCHEMES ======== */
+/// Fills a message out to whole blocks, and checks and removes that filling again.
+/// Every ECB and CBC function on a [`BlockCipher`](super::block::BlockCipher) takes one
+/// explicitly, [`NoPadding`] or [`Pkcs7`] being the usual choices; the AES shorthands always use
+/// PKCS#7.
+///
+/// ```
+/// use cryptopals::{
+///     aes::padding::{AnsiX923, Iso10126, Iso7816, NoPadding, Padding, Pkcs7, ZeroPadding},
+///     Error,
+/// };
+///
+/// let message = b"YELLOW SUBMARINE";
+/// assert_eq!(Pkcs7.pad(&message[..12], 16)?, b"YELLOW SUBMA\x04\x04\x04\x04");
+/// assert_eq!(AnsiX923.pad(&message[..12], 16)?, b"YELLOW SUBMA\0\0\0\x04");
+/// assert_eq!(Iso7816.pad(&message[..12], 16)?, b"YELLOW SUBMA\x80\0\0\0");
+/// assert_eq!(ZeroPadding.pad(&message[..12], 16)?, b"YELLOW SUBMA\0\0\0\0");
+///
+/// // Every scheme but zero padding adds a whole block to a message that is already aligned
+/// for padding in [&Pkcs7 as &dyn Padding, &AnsiX923, &Iso7816, &Iso10126] {
+///     let padded = padding.pad(message, 16)?;
+///     assert_eq!(padded.len(), 32);
+///     assert_eq!(padding.unpad(&padded, 16)?, message);
+/// }
+/// assert_eq!(ZeroPadding.pad(message, 16)?, message);
+///
+/// // Each scheme has its own idea of valid padding
+/// assert!(Pkcs7.unpad(b"YELLOW SUBMA\0\0\0\x04", 16).is_err());
+/// assert!(AnsiX923.unpad(b"YELLOW SUBMA\0\0\0\x04", 16).is_ok());
+/// assert!(Iso7816.unpad(b"YELLOW SUBMA\0\0\0\x04", 16).is_err());
+/// assert!(Iso10126.unpad(b"YELLOW SUBMA\0\0\0\x04", 16).is_ok());
+///
+/// // Blocks are 1 to 255 bytes, as the length byte limits them; any other size fails both ways
+/// for padding in [&NoPadding as &dyn Padding, &Pkcs7, &AnsiX923, &Iso7816, &Iso10126, &ZeroPadding] {
+///     assert_eq!(padding.pad(message, 0), Err(Error::InvalidBlockSize { size: 0 }));
+///     assert_eq!(padding.pad(message, 256), Err(Error::InvalidBlockSize { size: 256 }));
+///     assert_eq!(padding.unpad(message, 0), Err(Error::InvalidBlockSize { size: 0 }));
+///     assert_eq!(padding.unpad(message, 256), Err(Error::InvalidBlockSize { size: 256 }));
+/// }
+/// # Ok::<(), cryptopals::Error>(())
+/// ```
+///
+/// Unpadding undoes padding for every block size and any message, up to a few blocks long.
+/// Zero padding is the exception, losing any zeros the message ends in.
+///
+/// ```
+/// use cryptopals::{
+///     aes::padding::{AnsiX923, Iso10126, Iso7816, Padding, Pkcs7, ZeroPadding},
+///     mt19937::MT19937,
+/// };
+/// use rand::{Rng, SeedableRng};
+///
+/// let mut rng = MT19937::seed_from_u64(25);
+/// for block_size in 1..=255 {
+///     for len in 0..=2 * block_size + 1 {
+///         let mut message: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+///         for padding in [&Pkcs7 as &dyn Padding, &AnsiX923, &Iso7816, &Iso10126] {
+///             let padded = padding.pad(&message, block_size)?;
+///             assert_eq!(padded.len() % block_size, 0);
+///             assert!(padded.len() > message.len());
+///             assert_eq!(padding.unpad(&padded, block_size)?, message);
+///         }
+///
+///         if let Some(last) = message.last_mut() {
+///             *last |= 1;
+///         }
+///         let padded = ZeroPadding.pad(&message, block_size)?;
+///         assert_eq!(padded.len() % block_size, 0);
+///         assert_eq!(ZeroPadding.unpad(&padded, block_size)?, message);
+///     }
+/// }
+/// # Ok::<(), cryptopals::Error>(())
+/// ```
+pub trait Padding {
+    fn pad(&self, message: &[u8], block_size: usize) -> Result<Vec<u8>, Error>;
+
+    fn unpad(&self, message: &[u8], block_size: usize) -> Result<Vec<u8>, Error>;
+}
+
+// No padding at all, for messages that are already whole blocks
+// The modes reject anything else rather than guess
+#[derive(Clone, Copy, Debug)]
+pub struct NoPadding;
+
+// n bytes of n, as used everywhere else in the crate
+#[derive(Clone, Copy, Debug)]
+pub struct Pkcs7;
+
+// Zeros, then a final byte giving the padding length
+#[derive(Clone, Copy, Debug)]
+pub struct AnsiX923;
+
+// ISO/IEC 7816-4: a 0x80 byte, then zeros
+#[derive(Clone, Copy, Debug)]
+pub struct Iso7816;
+
+// Random bytes, then a final byte giving the padding length
+// Only that last byte can be checked
+#[derive(Clone, Copy, Debug)]
+pub struct Iso10126;
+
+// Zeros, and only as many as needed, so a message ending in zeros loses them when unpadded
+// Unpadding never fails
+#[derive(Clone, Copy, Debug)]
+pub struct ZeroPadding;
+
+impl Padding for NoPadding {
+    fn pad(&self, message: &[u8], block_size: usize) -> Result<Vec<u8>, Error> {
+        check_block_size(block_size)?;
+        Ok(message.to_vec())
+    }
+
+    fn unpad(&self, message: &[u8], block_size: usize) -> Result<Vec<u8>, Error> {
+        check_block_size(block_size)?;
+        Ok(message.to_vec())
+    }
+}
+
+impl Padding for Pkcs7 {
+    fn pad(&self, message: &[u8], block_size: usize) -> Result<Vec<u8>, Error> {
+        super::pad_pkcs7(message, block_size)
+    }
+
+    fn unpad(&self, message: &[u8], block_size: usize) -> Result<Vec<u8>, Error> {
+        check_block_size(block_size)?;
+
+        // Both checks always run, so the time taken doesn't say which failed
+        let fits_block = message.last().is_some_and(|&n| n as usize <= block_size);
+        if super::validate_pkcs7(message) & fits_block {
+            Ok(message[..message.len() - *message.last().unwrap() as usize].to_vec())
+        } else {
+            Err(Error::BadPadding)
+        }
+    }
+}
+
+impl Padding for AnsiX923 {
+    fn pad(&self, message: &[u8], block_size: usize) -> Result<Vec<u8>, Error> {
+        let pad_length = pad_length(message, block_size)?;
+        let mut padded = message.to_vec();
+        padded.resize(message.len() + pad_length - 1, 0);
+        padded.push(pad_length as u8);
+        Ok(padded)
+    }
+
+    fn unpad(&self, message: &[u8], block_size: usize) -> Result<Vec<u8>, Error> {
+        let pad_length = check_length_byte(message, block_size)?;
+        let (message, padding) = message.split_at(message.len() - pad_length);
+        if padding[..pad_length - 1].iter().all(|&b| b == 0) {
+            Ok(message.to_vec())
+        } else {
+            Err(Error::BadPadding)
+        }
+    }
+}
+
+impl Padding for Iso7816 {
+    fn pad(&self, message: &[u8], block_size: usize) -> Result<Vec<u8>, Error> {
+        let pad_length = pad_length(message, block_size)?;
+        let mut padded = message.to_vec();
+        padded.push(0x80);
+        padded.resize(message.len() + pad_length, 0);
+        Ok(padded)
+    }
+
+    fn unpad(&self, message: &[u8], block_size: usize) -> Result<Vec<u8>, Error> {
+        check_block_size(block_size)?;
+
+        // The 0x80 is the last non-zero byte, and must be in the last block
+        let marker = message
+            .iter()
+            .rposition(|&b| b != 0)
+            .filter(|&i| message[i] == 0x80 && message.len() - i <= block_size)
+            .ok_or(Error::BadPadding)?;
+        Ok(message[..marker].to_vec())
+    }
+}
+
+impl Padding for Iso10126 {
+    fn pad(&self, message: &[u8], block_size: usize) -> Result<Vec<u8>, Error> {
+        let pad_length = pad_length(message, block_size)?;
+        let mut rng = thread_rng();
+        let mut padded = message.to_vec();
+        padded.extend((1..pad_length).map(|_| rng.gen::<u8>()));
+        padded.push(pad_length as u8);
+        Ok(padded)
+    }
+
+    fn unpad(&self, message: &[u8], block_size: usize) -> Result<Vec<u8>, Error> {
+        let pad_length = check_length_byte(message, block_size)?;
+        Ok(message[..message.len() - pad_length].to_vec())
+    }
+}
+
+impl Padding for ZeroPadding {
+    fn pad(&self, message: &[u8], block_size: usize) -> Result<Vec<u8>, Error> {
+        check_block_size(block_size)?;
+        let mut padded = message.to_vec();
+        padded.resize(message.len().div_ceil(block_size) * block_size, 0);
+        Ok(padded)
+    }
+
+    fn unpad(&self, message: &[u8], block_size: usize) -> Result<Vec<u8>, Error> {
+        check_block_size(block_size)?;
+
+        let zeros = message
+            .iter()
+            .rev()
+            .take(block_size - 1)
+            .take_while(|&&b| b == 0)
+            .count();
+        Ok(message[..message.len() - zeros].to_vec())
+    }
+}
+
+// Blocks can't be empty, and padding lengths have to fit in a byte
+pub(super) fn check_block_size(block_size: usize) -> Result<(), Error> {
+    if (1..256).contains(&block_size) {
+        Ok(())
+    } else {
+        Err(Error::InvalidBlockSize { size: block_size })
+    }
+}
+
+// Always at least one byte, so there is always padding to remove
+fn pad_length(message: &[u8], block_size: usize) -> Result<usize, Error> {
+    check_block_size(block_size)?;
+    Ok(block_size - message.len() % block_size)
+}
+
+// The final byte as a padding length, if it is one
+fn check_length_byte(message: &[u8], block_size: usize) -> Result<usize, Error> {
+    check_block_size(block_size)?;
+
+    match message.last() {
+        Some(&n) if n != 0 && n as usize <= block_size && n as usize <= message.len() => {
+            Ok(n as usize)
+        }
+        _ => Err(Error::BadPadding),
+    }
+}
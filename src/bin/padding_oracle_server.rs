@@ -0,0 +1,45 @@
+use std::{env, process};
+
+use cryptopals::aes::cbc::padding_oracle::server::PaddingOracleServer;
+
+const USAGE: &str = "Usage:
+    padding_oracle_server [<addr>] [--secret <text>]";
+
+const DEFAULT_ADDR: &str = "127.0.0.1:8080";
+const DEFAULT_SECRET: &str = "Now that the party is jumping";
+
+// Serves the padding oracle over HTTP until killed
+fn main() {
+    let mut addr = None;
+    let mut secret = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--secret" => match args.next() {
+                Some(text) => secret = Some(text),
+                None => exit_with(&format!("Missing text after --secret\n{}", USAGE)),
+            },
+            _ if addr.is_none() && !arg.starts_with('-') => addr = Some(arg),
+            _ => exit_with(&format!("Unexpected argument: {}\n{}", arg, USAGE)),
+        }
+    }
+    let addr = addr.as_deref().unwrap_or(DEFAULT_ADDR);
+    let secret = secret.as_deref().unwrap_or(DEFAULT_SECRET);
+
+    let server = match PaddingOracleServer::bind(addr, secret.as_bytes()) {
+        Ok(server) => server,
+        Err(err) => exit_with(&format!("Could not bind {}: {}", addr, err)),
+    };
+
+    println!("Listening on http://{}", addr);
+    println!("  GET /       sets a token cookie");
+    println!("  GET /check  200 if the token cookie has valid padding, 500 if not");
+    println!("Token: {}", server.token());
+    server.run();
+}
+
+fn exit_with(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
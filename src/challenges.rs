@@ -0,0 +1,856 @@
+use std::{error, time::Duration};
+
+use cryptopals::{
+    aes::{
+        self,
+        padding::{Iso7816, Padding},
+    },
+    hash::{sha1::Sha1, HashFunction},
+    mac,
+    mt19937::{
+        self,
+        time_seed::{self, Clock, SimulatedClock, SystemClock},
+    },
+    oracle::Metered,
+    util, xor, Error,
+};
+use rand::{seq::SliceRandom, thread_rng, Rng};
+
+/* ======== CHALLENGE REGISTRY ======== */
+pub type Report = Vec<(&'static str, String)>;
+pub type ChallengeResult = Result<Report, Box<dyn error::Error>>;
+
+pub struct Challenge {
+    pub set: u8,
+    pub number: u8,
+    pub title: &'static str,
+    // Default input file in assets/, used when no --input is given
+    pub asset: Option<&'static str>,
+    pub run: fn(Option<&str>) -> ChallengeResult,
+}
+
+pub const CHALLENGES: &[Challenge] = &[
+    Challenge {
+        set: 1,
+        number: 1,
+        title: "Convert hex to base64",
+        asset: None,
+        run: hex_to_base64,
+    },
+    Challenge {
+        set: 1,
+        number: 2,
+        title: "Fixed XOR",
+        asset: None,
+        run: fixed_xor,
+    },
+    Challenge {
+        set: 1,
+        number: 3,
+        title: "Single-byte XOR cipher",
+        asset: None,
+        run: single_byte_xor,
+    },
+    Challenge {
+        set: 1,
+        number: 4,
+        title: "Detect single-character XOR",
+        asset: Some("4.txt"),
+        run: detect_single_byte_xor,
+    },
+    Challenge {
+        set: 1,
+        number: 5,
+        title: "Implement repeating-key XOR",
+        asset: None,
+        run: repeating_key_xor,
+    },
+    Challenge {
+        set: 1,
+        number: 6,
+        title: "Break repeating-key XOR",
+        asset: Some("6.txt"),
+        run: break_repeating_key_xor,
+    },
+    Challenge {
+        set: 1,
+        number: 7,
+        title: "AES in ECB mode",
+        asset: Some("7.txt"),
+        run: aes_ecb,
+    },
+    Challenge {
+        set: 1,
+        number: 8,
+        title: "Detect AES in ECB mode",
+        asset: Some("8.txt"),
+        run: detect_aes_ecb,
+    },
+    Challenge {
+        set: 2,
+        number: 9,
+        title: "Implement PKCS#7 padding",
+        asset: None,
+        run: pkcs7_padding,
+    },
+    Challenge {
+        set: 2,
+        number: 10,
+        title: "Implement CBC mode",
+        asset: Some("10.txt"),
+        run: aes_cbc,
+    },
+    Challenge {
+        set: 2,
+        number: 11,
+        title: "An ECB/CBC detection oracle",
+        asset: None,
+        run: detect_aes_mode,
+    },
+    Challenge {
+        set: 2,
+        number: 12,
+        title: "Byte-at-a-time ECB decryption (Simple)",
+        asset: None,
+        run: byte_at_a_time_ecb,
+    },
+    Challenge {
+        set: 2,
+        number: 13,
+        title: "ECB cut-and-paste",
+        asset: None,
+        run: ecb_cut_and_paste,
+    },
+    Challenge {
+        set: 2,
+        number: 14,
+        title: "Byte-at-a-time ECB decryption (Harder)",
+        asset: None,
+        run: byte_at_a_time_ecb_padded,
+    },
+    Challenge {
+        set: 2,
+        number: 15,
+        title: "PKCS#7 padding validation",
+        asset: None,
+        run: pkcs7_validation,
+    },
+    Challenge {
+        set: 2,
+        number: 16,
+        title: "CBC bitflipping attacks",
+        asset: None,
+        run: cbc_bitflipping,
+    },
+    Challenge {
+        set: 3,
+        number: 17,
+        title: "The CBC padding oracle",
+        asset: None,
+        run: cbc_padding_oracle,
+    },
+    Challenge {
+        set: 3,
+        number: 18,
+        title: "Implement CTR, the stream cipher mode",
+        asset: None,
+        run: aes_ctr,
+    },
+    Challenge {
+        set: 3,
+        number: 19,
+        title: "Break fixed-nonce CTR mode using substitutions",
+        asset: None,
+        run: fixed_nonce_ctr_substitution,
+    },
+    Challenge {
+        set: 3,
+        number: 20,
+        title: "Break fixed-nonce CTR statistically",
+        asset: Some("20.txt"),
+        run: fixed_nonce_ctr_statistically,
+    },
+    Challenge {
+        set: 3,
+        number: 21,
+        title: "Implement the MT19937 Mersenne Twister RNG",
+        asset: None,
+        run: mt19937_outputs,
+    },
+    Challenge {
+        set: 3,
+        number: 22,
+        title: "Crack an MT19937 seed",
+        asset: None,
+        run: mt19937_time_seed,
+    },
+    Challenge {
+        set: 3,
+        number: 23,
+        title: "Clone an MT19937 RNG from its output",
+        asset: None,
+        run: mt19937_clone,
+    },
+    Challenge {
+        set: 3,
+        number: 24,
+        title: "Create the MT19937 stream cipher and break it",
+        asset: None,
+        run: mt19937_stream_cipher,
+    },
+    Challenge {
+        set: 4,
+        number: 25,
+        title: "Break \"random access read/write\" AES CTR",
+        // 25.txt is the same file as 7.txt
+        asset: Some("7.txt"),
+        run: ctr_random_access,
+    },
+    Challenge {
+        set: 4,
+        number: 26,
+        title: "CTR bitflipping",
+        asset: None,
+        run: ctr_bitflipping,
+    },
+    Challenge {
+        set: 4,
+        number: 27,
+        title: "Recover the key from CBC with IV=Key",
+        asset: None,
+        run: cbc_key_as_iv,
+    },
+    Challenge {
+        set: 4,
+        number: 28,
+        title: "Implement a SHA-1 keyed MAC",
+        asset: None,
+        run: sha1_keyed_mac,
+    },
+    Challenge {
+        set: 4,
+        number: 29,
+        title: "Break a SHA-1 keyed MAC using length extension",
+        asset: None,
+        run: sha1_length_extension,
+    },
+    Challenge {
+        set: 4,
+        number: 30,
+        title: "Break an MD4 keyed MAC using length extension",
+        asset: None,
+        run: md4_length_extension,
+    },
+    Challenge {
+        set: 4,
+        number: 31,
+        title: "Implement and break HMAC-SHA1 with an artificial timing leak",
+        asset: None,
+        run: hmac_timing_leak_50ms,
+    },
+    Challenge {
+        set: 4,
+        number: 32,
+        title: "Break HMAC-SHA1 with a slightly less artificial timing leak",
+        asset: None,
+        run: hmac_timing_leak_5ms,
+    },
+];
+
+pub fn find_challenge(set: u8, number: u8) -> Option<&'static Challenge> {
+    CHALLENGES
+        .iter()
+        .find(|challenge| challenge.set == set && challenge.number == number)
+}
+
+/* ======== INPUT HELPERS ======== */
+const YELLOW_SUBMARINE: &[u8; 16] = b"YELLOW SUBMARINE";
+
+fn lines(input: &str) -> impl Iterator<Item = &str> {
+    input.lines().map(str::trim).filter(|line| !line.is_empty())
+}
+
+// Base64 files are wrapped over multiple lines
+fn base64_file_to_bytes(input: &str) -> Result<Vec<u8>, Error> {
+    util::base64_to_bytes(&lines(input).collect::<String>())
+}
+
+/* ======== SET 1 ======== */
+fn hex_to_base64(input: Option<&str>) -> ChallengeResult {
+    let hex = input.unwrap_or("49276d206b696c6c696e6720796f757220627261696e206c696b65206120706f69736f6e6f7573206d757368726f6f6d").trim();
+
+    Ok(vec![(
+        "base64",
+        util::bytes_to_base64(&util::hex_to_bytes(hex)?),
+    )])
+}
+
+fn fixed_xor(input: Option<&str>) -> ChallengeResult {
+    let input = input
+        .unwrap_or("1c0111001f010116061a024b53535009181c\n686974207468652062756c6c277320657965");
+    let buffers: Vec<Vec<u8>> = lines(input)
+        .map(util::hex_to_bytes)
+        .collect::<Result<_, _>>()?;
+    if buffers.len() != 2 || buffers[0].len() != buffers[1].len() {
+        return Err("Expected two equal length hex strings".into());
+    }
+
+    Ok(vec![(
+        "xor",
+        util::bytes_to_hex(&xor::xor(&buffers[0], &buffers[1])),
+    )])
+}
+
+fn single_byte_xor(input: Option<&str>) -> ChallengeResult {
+    let hex = input
+        .unwrap_or("1b37373331363f78151b7f2b783431333d78397828372d363c78373e783a393b3736")
+        .trim();
+
+    let res = xor::decrypt_single_byte_xor(&util::hex_to_bytes(hex)?)
+        .ok_or("Could not decrypt ciphertext")?;
+
+    Ok(vec![
+        ("key", res.key.to_string()),
+        ("message", util::bytes_to_ascii(&res.message)),
+    ])
+}
+
+fn detect_single_byte_xor(input: Option<&str>) -> ChallengeResult {
+    let ciphertexts: Vec<Vec<u8>> = lines(input.unwrap_or_default())
+        .map(util::hex_to_bytes)
+        .collect::<Result<_, _>>()?;
+
+    let res = xor::detect_single_byte_xor(&ciphertexts).ok_or("No ciphertexts found")?;
+
+    Ok(vec![
+        ("key", res.key.to_string()),
+        (
+            "message",
+            util::bytes_to_ascii(&res.message).trim_end().to_string(),
+        ),
+    ])
+}
+
+fn repeating_key_xor(input: Option<&str>) -> ChallengeResult {
+    let plaintext = input
+        .unwrap_or("Burning 'em, if you ain't quick and nimble\nI go crazy when I hear a cymbal");
+
+    Ok(vec![(
+        "ciphertext",
+        util::bytes_to_hex(&xor::repeating_key_xor(
+            &util::ascii_to_bytes(plaintext),
+            b"ICE",
+        )),
+    )])
+}
+
+fn break_repeating_key_xor(input: Option<&str>) -> ChallengeResult {
+    let ciphertext = base64_file_to_bytes(input.unwrap_or_default())?;
+    let freqs = util::generate_frequency_map();
+
+    // Try the best few keysizes, since the edit distance alone can be misleading
+    let res = xor::find_keysize_candidates(&ciphertext)?
+        .into_iter()
+        .take(3)
+        .map(|keysize| xor::decrypt_repeating_key_xor(&ciphertext, keysize))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .max_by(|a, b| {
+            util::calculate_monogram_fitness(&a.message, &freqs)
+                .partial_cmp(&util::calculate_monogram_fitness(&b.message, &freqs))
+                .unwrap()
+        })
+        .ok_or("No keysize candidates found")?;
+
+    Ok(vec![
+        ("keysize", res.key.len().to_string()),
+        ("key", util::bytes_to_ascii(&res.key)),
+        ("message", util::bytes_to_ascii(&res.message)),
+    ])
+}
+
+fn aes_ecb(input: Option<&str>) -> ChallengeResult {
+    let ciphertext = base64_file_to_bytes(input.unwrap_or_default())?;
+    let plaintext = aes::ecb::decrypt_aes_ecb(&ciphertext, YELLOW_SUBMARINE)?;
+
+    Ok(vec![("plaintext", util::bytes_to_ascii(&plaintext))])
+}
+
+fn detect_aes_ecb(input: Option<&str>) -> ChallengeResult {
+    let ciphertexts: Vec<Vec<u8>> = lines(input.unwrap_or_default())
+        .map(util::hex_to_bytes)
+        .collect::<Result<_, _>>()?;
+
+    let detected = aes::ecb::detect_aes_ecb(&ciphertexts)?;
+    let line = ciphertexts.iter().position(|c| *c == detected).unwrap() + 1;
+
+    Ok(vec![
+        ("line", line.to_string()),
+        ("ciphertext", util::bytes_to_hex(&detected)),
+    ])
+}
+
+/* ======== SET 2 ======== */
+fn pkcs7_padding(input: Option<&str>) -> ChallengeResult {
+    let message = input.unwrap_or("YELLOW SUBMARINE");
+    let padded = aes::pad_pkcs7(&util::ascii_to_bytes(message), 20)?;
+
+    Ok(vec![(
+        "padded",
+        format!("{:?}", util::bytes_to_ascii(&padded)),
+    )])
+}
+
+fn aes_cbc(input: Option<&str>) -> ChallengeResult {
+    let ciphertext = base64_file_to_bytes(input.unwrap_or_default())?;
+    let plaintext = aes::cbc::decrypt_aes_cbc(&ciphertext, YELLOW_SUBMARINE, &[0; 16])?;
+
+    Ok(vec![("plaintext", util::bytes_to_ascii(&plaintext))])
+}
+
+fn detect_aes_mode(_: Option<&str>) -> ChallengeResult {
+    let trials = 100;
+    let correct = (0..trials)
+        .filter(|_| {
+            let (ciphertext, is_ecb) = aes::random_aes_mode_encrypt(&[0; 16 * 3]);
+            aes::detect_aes_ecb_from_oracle(&|_: &[u8]| ciphertext.clone()) == Ok(is_ecb)
+        })
+        .count();
+
+    Ok(vec![
+        ("trials", trials.to_string()),
+        ("detected correctly", correct.to_string()),
+    ])
+}
+
+const UNKNOWN_STRING: &str = "Um9sbGluJyBpbiBteSA1LjAKV2l0aCBteSByYWctdG9wIGRvd24gc28gbXkgaGFpciBjYW4gYmxvdwpUaGUgZ2lybGllcyBvbiBzdGFuZGJ5IHdhdmluZyBqdXN0IHRvIHNheSBoaQpEaWQgeW91IHN0b3A/IE5vLCBJIGp1c3QgZHJvdmUgYnkK";
+
+fn byte_at_a_time_ecb(input: Option<&str>) -> ChallengeResult {
+    let unknown_string = match input {
+        Some(input) => util::ascii_to_bytes(input),
+        None => util::base64_to_bytes(UNKNOWN_STRING)?,
+    };
+
+    let oracle = Metered::new(aes::ecb::gen_aes_ecb_oracle(&unknown_string));
+    let recovered = aes::ecb::decrypt_aes_ecb_with_oracle(&oracle)?;
+
+    Ok(vec![
+        ("recovered", util::bytes_to_ascii(&recovered)),
+        ("oracle queries", oracle.queries().to_string()),
+        (
+            "bytes per query",
+            format!("{:.4}", oracle.bytes_per_query(recovered.len())),
+        ),
+    ])
+}
+
+fn ecb_cut_and_paste(_: Option<&str>) -> ChallengeResult {
+    let plaintext =
+        util::bytes_to_ascii(&aes::ecb::cut_and_paste::ecb_cut_and_paste_admin_profile());
+    let role = aes::parse_query_string(&plaintext)?
+        .into_iter()
+        .find(|(key, _)| key == "role")
+        .map(|(_, value)| value)
+        .unwrap_or_default();
+
+    Ok(vec![("profile", plaintext), ("role", role)])
+}
+
+fn byte_at_a_time_ecb_padded(input: Option<&str>) -> ChallengeResult {
+    let unknown_string = match input {
+        Some(input) => util::ascii_to_bytes(input),
+        None => util::base64_to_bytes(UNKNOWN_STRING)?,
+    };
+
+    let oracle = Metered::new(aes::ecb::gen_aes_ecb_oracle_padded(&unknown_string));
+    let recovered = aes::ecb::decrypt_aes_ecb_with_oracle(&oracle)?;
+
+    Ok(vec![
+        ("recovered", util::bytes_to_ascii(&recovered)),
+        ("oracle queries", oracle.queries().to_string()),
+        (
+            "bytes per query",
+            format!("{:.4}", oracle.bytes_per_query(recovered.len())),
+        ),
+    ])
+}
+
+fn pkcs7_validation(_: Option<&str>) -> ChallengeResult {
+    let cases: [&[u8]; 3] = [
+        b"ICE ICE BABY\x04\x04\x04\x04",
+        b"ICE ICE BABY\x05\x05\x05\x05",
+        b"ICE ICE BABY\x01\x02\x03\x04",
+    ];
+
+    Ok(cases
+        .iter()
+        .map(|&case| {
+            let result = match aes::strip_pkcs7(case) {
+                Ok(stripped) => format!("{:?}", util::bytes_to_ascii(&stripped)),
+                Err(err) => err.to_string(),
+            };
+            (
+                "result",
+                format!("{:?} => {}", util::bytes_to_ascii(case), result),
+            )
+        })
+        .collect())
+}
+
+fn cbc_bitflipping(_: Option<&str>) -> ChallengeResult {
+    let plaintext = aes::cbc::bitflipping::cbc_bitflipping_attack_admin()?;
+
+    Ok(vec![
+        (
+            "plaintext",
+            format!("{:?}", util::bytes_to_ascii(&plaintext)),
+        ),
+        (
+            "admin",
+            aes::cbc::bitflipping::is_admin(&plaintext).to_string(),
+        ),
+    ])
+}
+
+/* ======== SET 3 ======== */
+const PADDING_ORACLE_STRINGS: &str = "MDAwMDAwTm93IHRoYXQgdGhlIHBhcnR5IGlzIGp1bXBpbmc=
+MDAwMDAxV2l0aCB0aGUgYmFzcyBraWNrZWQgaW4gYW5kIHRoZSBWZWdhJ3MgYXJlIHB1bXBpbic=
+MDAwMDAyUXVpY2sgdG8gdGhlIHBvaW50LCB0byB0aGUgcG9pbnQsIG5vIGZha2luZw==
+MDAwMDAzQ29va2luZyBNQydzIGxpa2UgYSBwb3VuZCBvZiBiYWNvbg==
+MDAwMDA0QnVybmluZyAnZW0sIGlmIHlvdSBhaW4ndCBxdWljayBhbmQgbmltYmxl
+MDAwMDA1SSBnbyBjcmF6eSB3aGVuIEkgaGVhciBhIGN5bWJhbA==
+MDAwMDA2QW5kIGEgaGlnaCBoYXQgd2l0aCBhIHNvdXBlZCB1cCB0ZW1wbw==
+MDAwMDA3SSdtIG9uIGEgcm9sbCwgaXQncyB0aW1lIHRvIGdvIHNvbG8=
+MDAwMDA4b2xsaW4nIGluIG15IGZpdmUgcG9pbnQgb2g=
+MDAwMDA5aXRoIG15IHJhZy10b3AgZG93biBzbyBteSBoYWlyIGNhbiBibG93";
+
+fn cbc_padding_oracle(input: Option<&str>) -> ChallengeResult {
+    let plaintexts: Vec<Vec<u8>> = lines(input.unwrap_or(PADDING_ORACLE_STRINGS))
+        .map(util::base64_to_bytes)
+        .collect::<Result<_, _>>()?;
+    let plaintext = plaintexts
+        .choose(&mut thread_rng())
+        .ok_or("No plaintexts found")?;
+
+    let (ciphertext, iv, oracle) = aes::cbc::padding_oracle::gen_aes_cbc_padding_oracle(plaintext);
+    let oracle = Metered::new(oracle);
+    let recovered = aes::cbc::padding_oracle::cbc_padding_oracle_attack(&ciphertext, &iv, &oracle)?;
+    let bytes_per_query = oracle.bytes_per_query(recovered.len());
+
+    // The parallel attack should agree, in fewer queries
+    let (recovered_parallel, parallel_queries) =
+        aes::cbc::padding_oracle::cbc_padding_oracle_attack_parallel(&ciphertext, &iv, &oracle)?;
+    let queries = oracle.queries() - parallel_queries;
+
+    // ISO/IEC 7816-4 padding leaks through its oracle just the same
+    let (iso_ciphertext, iso_iv, iso_oracle) =
+        aes::cbc::padding_oracle::gen_aes_cbc_padding_oracle_with_padding(plaintext, Iso7816);
+    let iso_recovered = aes::cbc::padding_oracle::cbc_iso7816_padding_oracle_attack(
+        &iso_ciphertext,
+        &iso_iv,
+        &iso_oracle,
+    )?;
+
+    // The oracle also encrypts, so forge a token and check it decrypts as intended
+    let (forged_iv, forged) =
+        aes::cbc::padding_oracle::cbc_padding_oracle_encrypt(b"role=admin", &oracle)?;
+    let (forged_plaintext, _) =
+        aes::cbc::padding_oracle::cbc_padding_oracle_attack_parallel(&forged, &forged_iv, &oracle)?;
+
+    Ok(vec![
+        ("ciphertext", util::bytes_to_hex(&ciphertext)),
+        (
+            "recovered",
+            util::bytes_to_ascii(&aes::strip_pkcs7(&recovered)?),
+        ),
+        ("oracle queries", queries.to_string()),
+        ("bytes per query", format!("{:.4}", bytes_per_query)),
+        ("parallel queries", parallel_queries.to_string()),
+        (
+            "parallel bytes per query",
+            format!("{:.4}", recovered.len() as f64 / parallel_queries as f64),
+        ),
+        (
+            "parallel matches",
+            (recovered_parallel == recovered).to_string(),
+        ),
+        (
+            "ISO 7816-4 recovered",
+            util::bytes_to_ascii(&Iso7816.unpad(&iso_recovered, 16)?),
+        ),
+        ("forged", util::bytes_to_hex(&[forged_iv, forged].concat())),
+        (
+            "forged decrypts to",
+            util::bytes_to_ascii(&aes::strip_pkcs7(&forged_plaintext)?),
+        ),
+    ])
+}
+
+fn aes_ctr(input: Option<&str>) -> ChallengeResult {
+    let ciphertext = util::base64_to_bytes(
+        input
+            .unwrap_or("L77na/nrFsKvynd6HzOoG7GHTLXsTVu9qvY/2syLXzhPweyyMTJULu/6/kXX0KSvoOLSFQ==")
+            .trim(),
+    )?;
+    let plaintext = aes::ctr::decrypt_aes_ctr(&ciphertext, YELLOW_SUBMARINE, &0u64.to_le_bytes())?;
+
+    Ok(vec![("plaintext", util::bytes_to_ascii(&plaintext))])
+}
+
+fn fixed_nonce_ctr_substitution(_: Option<&str>) -> ChallengeResult {
+    // The guesses are printed as they are made
+    aes::ctr::fixed_nonce::decrypt_fixed_nonce_ctr_substitution();
+
+    Ok(vec![])
+}
+
+fn fixed_nonce_ctr_statistically(input: Option<&str>) -> ChallengeResult {
+    let plaintexts: Vec<Vec<u8>> = lines(input.unwrap_or_default())
+        .map(util::base64_to_bytes)
+        .collect::<Result<_, _>>()?;
+
+    let ciphertexts =
+        aes::ctr::fixed_nonce::encrypt_fixed_nonce_ctr(&plaintexts, &0u64.to_le_bytes());
+    let recovered = aes::ctr::fixed_nonce::decrypt_fixed_nonce_ctr_statistically(&ciphertexts)?;
+
+    Ok(recovered
+        .iter()
+        .map(|bytes| ("recovered", util::bytes_to_ascii(bytes)))
+        .collect())
+}
+
+fn mt19937_outputs(input: Option<&str>) -> ChallengeResult {
+    let seed: u32 = input.unwrap_or("5489").trim().parse()?;
+    let mut rng = mt19937::MT19937::new(seed);
+
+    Ok(vec![
+        ("seed", seed.to_string()),
+        ("1st output", rng.next().unwrap().to_string()),
+        ("10000th output", rng.nth(9998).unwrap().to_string()),
+    ])
+}
+
+// The waiting is simulated, starting from the real time
+fn mt19937_time_seed(_: Option<&str>) -> ChallengeResult {
+    let clock = SimulatedClock::new(SystemClock.now());
+    let output = time_seed::time_seeded_output(&clock);
+
+    let seed = time_seed::crack_time_seed_with_clock(&clock, output, 3600)
+        .ok_or("No seed in the last hour")?;
+
+    Ok(vec![
+        ("first output", output.to_string()),
+        ("seed", seed.to_string()),
+        (
+            "verified",
+            (mt19937::MT19937::new(seed).next() == Some(output)).to_string(),
+        ),
+    ])
+}
+
+fn mt19937_clone(input: Option<&str>) -> ChallengeResult {
+    let seed: u32 = match input {
+        Some(seed) => seed.trim().parse()?,
+        None => thread_rng().gen(),
+    };
+    let mut rng = mt19937::MT19937::new(seed);
+
+    let mut clone =
+        mt19937::clone_from_outputs(rng.by_ref().take(624)).ok_or("Not enough outputs to clone")?;
+
+    let predicted: Vec<u32> = clone.by_ref().take(1000).collect();
+    let actual: Vec<u32> = rng.by_ref().take(1000).collect();
+
+    Ok(vec![
+        ("seed", seed.to_string()),
+        ("next output", actual[0].to_string()),
+        ("predicted", predicted[0].to_string()),
+        ("next 1000 match", (predicted == actual).to_string()),
+    ])
+}
+
+fn mt19937_stream_cipher(_: Option<&str>) -> ChallengeResult {
+    let known = [b'A'; 14];
+    let encrypt = mt19937::stream::gen_mt19937_prefix_oracle();
+    let ciphertext = encrypt(&known);
+
+    let seed = mt19937::stream::recover_mt19937_seed(&ciphertext, &known)
+        .ok_or("No 16-bit seed decrypts the known plaintext")?;
+    let plaintext = mt19937::stream::decrypt_mt19937(&ciphertext, seed);
+
+    let token = mt19937::stream::password_reset_token(&SystemClock);
+    let random_token: [u8; 16] = thread_rng().gen();
+
+    Ok(vec![
+        ("seed", seed.to_string()),
+        (
+            "plaintext",
+            format!("{:?}", util::bytes_to_ascii(&plaintext)),
+        ),
+        ("token", util::bytes_to_hex(&token)),
+        (
+            "token time seeded",
+            mt19937::stream::is_time_seeded_token(&token, &SystemClock, 3600).to_string(),
+        ),
+        ("random token", util::bytes_to_hex(&random_token)),
+        (
+            "random token time seeded",
+            mt19937::stream::is_time_seeded_token(&random_token, &SystemClock, 3600).to_string(),
+        ),
+    ])
+}
+
+/* ======== SET 4 ======== */
+fn ctr_random_access(input: Option<&str>) -> ChallengeResult {
+    let ciphertext = base64_file_to_bytes(input.unwrap_or_default())?;
+    let plaintext = aes::ecb::decrypt_aes_ecb(&ciphertext, YELLOW_SUBMARINE)?;
+
+    let (ciphertext, edit) = aes::ctr::random_access::gen_aes_ctr_edit_oracle(&plaintext);
+    let recovered = aes::ctr::random_access::recover_ctr_plaintext_with_edit(&ciphertext, edit)?;
+
+    Ok(vec![
+        ("recovered", util::bytes_to_ascii(&recovered)),
+        ("matches", (recovered == plaintext).to_string()),
+    ])
+}
+
+fn ctr_bitflipping(_: Option<&str>) -> ChallengeResult {
+    let plaintext = aes::ctr::bitflipping::ctr_bitflipping_attack_admin()?;
+
+    Ok(vec![
+        (
+            "plaintext",
+            format!("{:?}", util::bytes_to_ascii(&plaintext)),
+        ),
+        (
+            "admin",
+            aes::cbc::bitflipping::is_admin(&plaintext).to_string(),
+        ),
+    ])
+}
+
+fn cbc_key_as_iv(_: Option<&str>) -> ChallengeResult {
+    let plaintext = util::ascii_to_bytes(&aes::cbc::bitflipping::profile_for(
+        "comment1=cooking%20MCs;userdata=",
+        "aaaaaaaaaaaaaaaa",
+        ";comment2=%20like%20a%20pound%20of%20bacon",
+    ));
+
+    let (encrypt, decrypt) = aes::cbc::key_as_iv::gen_aes_cbc_key_as_iv_oracles();
+    let ciphertext = encrypt(&plaintext);
+
+    let key = aes::cbc::key_as_iv::recover_key_as_iv(&ciphertext, &decrypt)?;
+
+    // The recovered key should decrypt the original ciphertext
+    let decrypted = aes::cbc::decrypt_aes_cbc(&ciphertext, &key, &key)?;
+
+    Ok(vec![
+        ("key", util::bytes_to_hex(&key)),
+        ("decrypted", util::bytes_to_ascii(&decrypted)),
+        ("matches", (decrypted == plaintext).to_string()),
+    ])
+}
+
+fn sha1_keyed_mac(input: Option<&str>) -> ChallengeResult {
+    let message = util::ascii_to_bytes(input.unwrap_or("Cooking MC's like a pound of bacon"));
+    let key: [u8; 16] = thread_rng().gen();
+
+    let mac = mac::secret_prefix_sha1(&key, &message);
+
+    // Any change to the message should be caught
+    let mut tampered = message.clone();
+    match tampered.first_mut() {
+        Some(byte) => *byte ^= 1,
+        None => tampered.push(0),
+    }
+
+    Ok(vec![
+        ("mac", util::bytes_to_hex(&mac)),
+        (
+            "verifies",
+            mac::verify_secret_prefix_sha1(&key, &message, &mac).to_string(),
+        ),
+        (
+            "tampered verifies",
+            mac::verify_secret_prefix_sha1(&key, &tampered, &mac).to_string(),
+        ),
+    ])
+}
+
+fn sha1_length_extension(_: Option<&str>) -> ChallengeResult {
+    let (forged, forged_mac) = mac::length_extension::sha1_length_extension_admin()?
+        .ok_or("No key length up to 64 bytes verified")?;
+
+    Ok(vec![
+        (
+            "forged message",
+            format!("{:?}", util::bytes_to_ascii(&forged)),
+        ),
+        ("forged mac", util::bytes_to_hex(&forged_mac)),
+        (
+            "admin",
+            mac::length_extension::has_admin_field(&forged).to_string(),
+        ),
+    ])
+}
+
+fn md4_length_extension(_: Option<&str>) -> ChallengeResult {
+    let (forged, forged_mac) = mac::length_extension::md4_length_extension_admin()?
+        .ok_or("No key length up to 64 bytes verified")?;
+
+    Ok(vec![
+        (
+            "forged message",
+            format!("{:?}", util::bytes_to_ascii(&forged)),
+        ),
+        ("forged mac", util::bytes_to_hex(&forged_mac)),
+        (
+            "admin",
+            mac::length_extension::has_admin_field(&forged).to_string(),
+        ),
+    ])
+}
+
+fn hmac_timing_leak_50ms(input: Option<&str>) -> ChallengeResult {
+    hmac_timing_leak(input, Duration::from_millis(50), 1)
+}
+
+fn hmac_timing_leak_5ms(input: Option<&str>) -> ChallengeResult {
+    hmac_timing_leak(input, Duration::from_millis(5), 5)
+}
+
+// The input, if any, is the file name to forge a signature for
+fn hmac_timing_leak(input: Option<&str>, delay: Duration, samples: usize) -> ChallengeResult {
+    let file = input.map(str::trim).unwrap_or("foo");
+    let key: [u8; 16] = thread_rng().gen();
+
+    let addr = mac::timing_leak::spawn_timing_leak_server::<Sha1>(&key, delay)?;
+    let (signature, timings) =
+        mac::timing_leak::recover_hmac_by_timing(addr, file, Sha1::OUTPUT_SIZE, samples)?;
+    let (valid, _) = mac::timing_leak::request_signature(addr, file, &signature)?;
+
+    // How far the right guess stood out from the next best, per byte
+    // Negative where a guess was accepted outright despite answering sooner
+    let margins: Vec<String> = timings
+        .iter()
+        .map(|timing| {
+            format!(
+                "{:.1}",
+                (timing.best.as_secs_f64() - timing.runner_up.as_secs_f64()) * 1000.0
+            )
+        })
+        .collect();
+
+    Ok(vec![
+        ("file", file.to_string()),
+        ("delay", format!("{:?}", delay)),
+        ("samples", samples.to_string()),
+        (
+            "expected",
+            util::bytes_to_hex(&mac::hmac::<Sha1>(&key, file.as_bytes())),
+        ),
+        ("recovered", util::bytes_to_hex(&signature)),
+        ("valid", valid.to_string()),
+        ("margins (ms)", margins.join(" ")),
+    ])
+}
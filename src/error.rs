@@ -0,0 +1,86 @@
+use std::fmt;
+
+use crate::util;
+
+/* ======== ERRORS ======== */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    // Position is the byte index of the offending char, or the length if the string is too short
+    InvalidHex { position: usize },
+    InvalidBase64 { position: usize },
+    BadPadding,
+    InvalidKeyLength { len: usize },
+    InvalidNonceLength { len: usize },
+    InvalidIvLength { len: usize },
+    InvalidDigestLength { len: usize },
+    InvalidCiphertextLength { len: usize },
+    // Only 1 to 255 bytes, as padding lengths are a byte
+    InvalidBlockSize { size: usize },
+    // Only without padding, when the plaintext isn't whole blocks
+    InvalidPlaintextLength { len: usize },
+    InvalidOffset { offset: usize },
+    // Nothing to attack, such as no ciphertexts at all
+    EmptyInput,
+    // Past the end of a Mersenne Twister state
+    InvalidStateIndex { index: usize },
+    // Two inputs that should be the same length aren't
+    LengthMismatch { expected: usize, len: usize },
+    MalformedQuery,
+    // Leaks the offending plaintext, as a careless server would
+    HighAscii { plaintext: Vec<u8> },
+    // An encryption oracle that doesn't behave as ECB over its input and a secret
+    NotEcb,
+    // No byte gave valid padding, so the oracle doesn't behave as a padding oracle should
+    NoValidPadding,
+    QueryBudgetExceeded { budget: usize },
+    // The oracle couldn't be reached, or gave no usable answer
+    OracleUnavailable { reason: String },
+    // Asked of a replayed oracle, but not in its transcript
+    UnrecordedQuery { input: Vec<u8> },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidHex { position } => write!(f, "Invalid hex at position {}", position),
+            Error::InvalidBase64 { position } => {
+                write!(f, "Invalid base64 at position {}", position)
+            }
+            Error::BadPadding => write!(f, "Invalid padding"),
+            Error::InvalidKeyLength { len } => write!(f, "Invalid key length {}", len),
+            Error::InvalidNonceLength { len } => write!(f, "Invalid nonce length {}", len),
+            Error::InvalidIvLength { len } => write!(f, "Invalid IV length {}", len),
+            Error::InvalidDigestLength { len } => write!(f, "Invalid digest length {}", len),
+            Error::InvalidCiphertextLength { len } => {
+                write!(f, "Invalid ciphertext length {}", len)
+            }
+            Error::InvalidBlockSize { size } => write!(f, "Invalid block size {}", size),
+            Error::InvalidPlaintextLength { len } => {
+                write!(f, "Invalid plaintext length {}", len)
+            }
+            Error::EmptyInput => write!(f, "Empty input"),
+            Error::InvalidStateIndex { index } => write!(f, "Invalid state index {}", index),
+            Error::InvalidOffset { offset } => write!(f, "Invalid offset {}", offset),
+            Error::LengthMismatch { expected, len } => {
+                write!(f, "Expected length {}, got {}", expected, len)
+            }
+            Error::MalformedQuery => write!(f, "Invalid query string format"),
+            Error::HighAscii { plaintext } => write!(
+                f,
+                "Invalid high-ASCII plaintext: {}",
+                util::bytes_to_hex(plaintext)
+            ),
+            Error::NotEcb => write!(f, "Oracle is not ECB"),
+            Error::NoValidPadding => write!(f, "No guess gave valid padding"),
+            Error::QueryBudgetExceeded { budget } => {
+                write!(f, "Oracle query budget of {} exceeded", budget)
+            }
+            Error::OracleUnavailable { reason } => write!(f, "Oracle unavailable: {}", reason),
+            Error::UnrecordedQuery { input } => {
+                write!(f, "Query not in transcript: {}", util::bytes_to_hex(input))
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
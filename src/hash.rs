@@ -0,0 +1,24 @@
+pub mod md4;
+pub mod sha1;
+
+/* ======== HASH FUNCTIONS ======== */
+// A Merkle-Damgard hash with a streaming API
+pub trait HashFunction: Clone {
+    const BLOCK_SIZE: usize;
+    const OUTPUT_SIZE: usize;
+
+    fn new() -> Self;
+
+    fn update(&mut self, data: &[u8]);
+
+    fn finalize(self) -> Vec<u8>;
+
+    fn digest(data: &[u8]) -> Vec<u8>
+    where
+        Self: Sized,
+    {
+        let mut hasher = Self::new();
+        hasher.update(data);
+        hasher.finalize()
+    }
+}
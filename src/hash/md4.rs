@@ -0,0 +1,177 @@
+use super::HashFunction;
+use crate::error::Error;
+
+/* ======== MD4 (RFC 1320) ======== */
+const BLOCK_SIZE: usize = 64;
+const OUTPUT_SIZE: usize = 16;
+
+const INITIAL_STATE: [u32; 4] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476];
+
+// Message word order and shift amounts for each round
+const WORD_ORDER: [[usize; 16]; 3] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [0, 4, 8, 12, 1, 5, 9, 13, 2, 6, 10, 14, 3, 7, 11, 15],
+    [0, 8, 4, 12, 2, 10, 6, 14, 1, 9, 5, 13, 3, 11, 7, 15],
+];
+const SHIFTS: [[u32; 4]; 3] = [[3, 7, 11, 19], [3, 5, 9, 13], [3, 9, 11, 15]];
+
+/// MD4 with its internal state exposed, for length-extension attacks.
+/// Unlike SHA-1, words and the padded length are little-endian.
+///
+/// ```
+/// use cryptopals::{hash::{md4::{self, Md4}, HashFunction}, util};
+///
+/// // RFC 1320 test suite
+/// let vectors = [
+///     ("", "31d6cfe0d16ae931b73c59d7e0c089c0"),
+///     ("a", "bde52cb31de33e46245e05fbdbd6fb24"),
+///     ("abc", "a448017aaf21d8525fc10ae87aa6729d"),
+///     ("message digest", "d9130a8164549fe818874806e1c7014b"),
+///     ("abcdefghijklmnopqrstuvwxyz", "d79e1c308aa5bbcdeea8ed63df412da9"),
+///     (
+///         "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789",
+///         "043f8582f241db351ce627e153e7f0e4",
+///     ),
+///     (
+///         "12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+///         "e33b4ddc9c38f2199c3e7b164fcc0536",
+///     ),
+/// ];
+/// for (message, digest) in vectors {
+///     assert_eq!(util::bytes_to_hex(&Md4::digest(message.as_bytes())), digest);
+/// }
+///
+/// // Resuming from a digest carries on as if the padded message had been hashed
+/// let glue = md4::padding(3);
+/// let state = Md4::state_from_digest(&Md4::digest(b"abc"))?;
+/// let mut resumed = Md4::from_state(state, 3 + glue.len() as u64);
+/// resumed.update(b"def");
+/// assert_eq!(
+///     resumed.finalize(),
+///     Md4::digest(&[b"abc".as_slice(), &glue, b"def"].concat())
+/// );
+///
+/// // A MAC of the wrong length can't be a state
+/// assert!(Md4::state_from_digest(&[0; 19]).is_err());
+/// # Ok::<(), cryptopals::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct Md4 {
+    state: [u32; 4],
+    // Bytes not yet compressed, always shorter than a block
+    buffer: Vec<u8>,
+    // Total bytes processed, including those in the buffer
+    length: u64,
+}
+
+impl Md4 {
+    // Resumes hashing from an arbitrary state, as if length bytes had been processed
+    // The length should be a multiple of the block size, i.e. include the padding
+    pub fn from_state(state: [u32; 4], length: u64) -> Md4 {
+        Md4 {
+            state,
+            buffer: Vec::with_capacity(BLOCK_SIZE),
+            length,
+        }
+    }
+
+    pub fn state(&self) -> [u32; 4] {
+        self.state
+    }
+
+    pub fn length(&self) -> u64 {
+        self.length
+    }
+
+    // The digest is just the final state, so it can be split back into it
+    pub fn state_from_digest(digest: &[u8]) -> Result<[u32; 4], Error> {
+        if digest.len() != OUTPUT_SIZE {
+            return Err(Error::InvalidDigestLength { len: digest.len() });
+        }
+
+        let mut state = [0u32; 4];
+        for (word, bytes) in state.iter_mut().zip(digest.chunks_exact(4)) {
+            *word = u32::from_le_bytes(bytes.try_into().unwrap());
+        }
+
+        Ok(state)
+    }
+}
+
+impl HashFunction for Md4 {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+    const OUTPUT_SIZE: usize = OUTPUT_SIZE;
+
+    fn new() -> Md4 {
+        Md4::from_state(INITIAL_STATE, 0)
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.length += data.len() as u64;
+        self.buffer.extend_from_slice(data);
+
+        let full_blocks = self.buffer.len() / BLOCK_SIZE * BLOCK_SIZE;
+        for block in self.buffer[..full_blocks].chunks_exact(BLOCK_SIZE) {
+            compress(&mut self.state, block);
+        }
+        self.buffer.drain(..full_blocks);
+    }
+
+    fn finalize(mut self) -> Vec<u8> {
+        let padding = padding(self.length);
+        self.update(&padding);
+        assert!(self.buffer.is_empty());
+
+        self.state
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect()
+    }
+}
+
+// Padding appended to a message of the given length in bytes:
+// 0x80, zeros up to 56 mod 64, then the length in bits as a little-endian u64
+pub fn padding(message_len: u64) -> Vec<u8> {
+    let zeros = (BLOCK_SIZE * 2 - 9 - (message_len as usize % BLOCK_SIZE)) % BLOCK_SIZE;
+
+    let mut padding = Vec::with_capacity(zeros + 9);
+    padding.push(0x80);
+    padding.extend(vec![0; zeros]);
+    padding.extend_from_slice(&(message_len.wrapping_mul(8)).to_le_bytes());
+
+    padding
+}
+
+fn compress(state: &mut [u32; 4], block: &[u8]) {
+    let mut x = [0u32; 16];
+    for (word, bytes) in x.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_le_bytes(bytes.try_into().unwrap());
+    }
+
+    let [mut a, mut b, mut c, mut d] = *state;
+    for round in 0..3 {
+        for i in 0..16 {
+            let (f, k) = match round {
+                0 => ((b & c) | (!b & d), 0),
+                1 => ((b & c) | (b & d) | (c & d), 0x5A827999),
+                _ => (b ^ c ^ d, 0x6ED9EBA1),
+            };
+
+            let temp = a
+                .wrapping_add(f)
+                .wrapping_add(x[WORD_ORDER[round][i]])
+                .wrapping_add(k)
+                .rotate_left(SHIFTS[round][i % 4]);
+
+            // Each step updates the next register along: a, d, c, b, a, ...
+            a = d;
+            d = c;
+            c = b;
+            b = temp;
+        }
+    }
+
+    for (s, v) in state.iter_mut().zip([a, b, c, d]) {
+        *s = s.wrapping_add(v);
+    }
+}
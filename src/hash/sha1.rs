@@ -0,0 +1,176 @@
+use super::HashFunction;
+use crate::error::Error;
+
+/* ======== SHA-1 (FIPS 180-4) ======== */
+const BLOCK_SIZE: usize = 64;
+const OUTPUT_SIZE: usize = 20;
+
+const INITIAL_STATE: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+/// SHA-1 with its internal state exposed, for length-extension attacks.
+///
+/// ```
+/// use cryptopals::{hash::{sha1::{self, Sha1}, HashFunction}, util};
+///
+/// // FIPS 180 examples
+/// assert_eq!(
+///     util::bytes_to_hex(&Sha1::digest(b"abc")),
+///     "a9993e364706816aba3e25717850c26c9cd0d89d"
+/// );
+/// assert_eq!(
+///     util::bytes_to_hex(&Sha1::digest(
+///         b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+///     )),
+///     "84983e441c3bd26ebaae4aa1f95129e5e54670f1"
+/// );
+/// assert_eq!(
+///     util::bytes_to_hex(&Sha1::digest(b"")),
+///     "da39a3ee5e6b4b0d3255bfef95601890afd80709"
+/// );
+///
+/// // Streaming gives the same result however the input is split
+/// let mut sha1 = Sha1::new();
+/// for _ in 0..1000 {
+///     sha1.update(&[b'a'; 1000]);
+/// }
+/// assert_eq!(
+///     util::bytes_to_hex(&sha1.finalize()),
+///     "34aa973cd4c4daa4f61eeb2bdbad27316534016f"
+/// );
+///
+/// // Resuming from a digest carries on as if the padded message had been hashed
+/// let glue = sha1::padding(3);
+/// let state = Sha1::state_from_digest(&Sha1::digest(b"abc"))?;
+/// let mut resumed = Sha1::from_state(state, 3 + glue.len() as u64);
+/// resumed.update(b"def");
+/// assert_eq!(
+///     resumed.finalize(),
+///     Sha1::digest(&[b"abc".as_slice(), &glue, b"def"].concat())
+/// );
+///
+/// // A MAC of the wrong length can't be a state
+/// assert!(Sha1::state_from_digest(&[0; 19]).is_err());
+/// # Ok::<(), cryptopals::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct Sha1 {
+    state: [u32; 5],
+    // Bytes not yet compressed, always shorter than a block
+    buffer: Vec<u8>,
+    // Total bytes processed, including those in the buffer
+    length: u64,
+}
+
+impl Sha1 {
+    // Resumes hashing from an arbitrary state, as if length bytes had been processed
+    // The length should be a multiple of the block size, i.e. include the padding
+    pub fn from_state(state: [u32; 5], length: u64) -> Sha1 {
+        Sha1 {
+            state,
+            buffer: Vec::with_capacity(BLOCK_SIZE),
+            length,
+        }
+    }
+
+    pub fn state(&self) -> [u32; 5] {
+        self.state
+    }
+
+    pub fn length(&self) -> u64 {
+        self.length
+    }
+
+    // The digest is just the final state, so it can be split back into it
+    pub fn state_from_digest(digest: &[u8]) -> Result<[u32; 5], Error> {
+        if digest.len() != OUTPUT_SIZE {
+            return Err(Error::InvalidDigestLength { len: digest.len() });
+        }
+
+        let mut state = [0u32; 5];
+        for (word, bytes) in state.iter_mut().zip(digest.chunks_exact(4)) {
+            *word = u32::from_be_bytes(bytes.try_into().unwrap());
+        }
+
+        Ok(state)
+    }
+}
+
+impl HashFunction for Sha1 {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+    const OUTPUT_SIZE: usize = OUTPUT_SIZE;
+
+    fn new() -> Sha1 {
+        Sha1::from_state(INITIAL_STATE, 0)
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.length += data.len() as u64;
+        self.buffer.extend_from_slice(data);
+
+        let full_blocks = self.buffer.len() / BLOCK_SIZE * BLOCK_SIZE;
+        for block in self.buffer[..full_blocks].chunks_exact(BLOCK_SIZE) {
+            compress(&mut self.state, block);
+        }
+        self.buffer.drain(..full_blocks);
+    }
+
+    fn finalize(mut self) -> Vec<u8> {
+        let padding = padding(self.length);
+        self.update(&padding);
+        assert!(self.buffer.is_empty());
+
+        self.state
+            .iter()
+            .flat_map(|word| word.to_be_bytes())
+            .collect()
+    }
+}
+
+// Padding appended to a message of the given length in bytes:
+// 0x80, zeros up to 56 mod 64, then the length in bits as a big-endian u64
+pub fn padding(message_len: u64) -> Vec<u8> {
+    let zeros = (BLOCK_SIZE * 2 - 9 - (message_len as usize % BLOCK_SIZE)) % BLOCK_SIZE;
+
+    let mut padding = Vec::with_capacity(zeros + 9);
+    padding.push(0x80);
+    padding.extend(vec![0; zeros]);
+    padding.extend_from_slice(&(message_len.wrapping_mul(8)).to_be_bytes());
+
+    padding
+}
+
+fn compress(state: &mut [u32; 5], block: &[u8]) {
+    let mut w = [0u32; 80];
+    for (i, bytes) in block.chunks_exact(4).enumerate() {
+        w[i] = u32::from_be_bytes(bytes.try_into().unwrap());
+    }
+    for i in 16..80 {
+        w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e] = *state;
+    for (i, &word) in w.iter().enumerate() {
+        let (f, k) = match i {
+            0..=19 => ((b & c) | (!b & d), 0x5A827999),
+            20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+            40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+            _ => (b ^ c ^ d, 0xCA62C1D6),
+        };
+
+        let temp = a
+            .rotate_left(5)
+            .wrapping_add(f)
+            .wrapping_add(e)
+            .wrapping_add(k)
+            .wrapping_add(word);
+        e = d;
+        d = c;
+        c = b.rotate_left(30);
+        b = a;
+        a = temp;
+    }
+
+    for (s, v) in state.iter_mut().zip([a, b, c, d, e]) {
+        *s = s.wrapping_add(v);
+    }
+}
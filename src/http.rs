@@ -0,0 +1,133 @@
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::TcpStream,
+};
+
+/* ======== MINIMAL HTTP ======== */
+// Just enough HTTP/1.1 for the local oracle servers and the clients attacking them
+// One request per connection, and no bodies either way
+
+pub struct Request {
+    pub method: String,
+    pub target: String,
+    pub headers: Vec<(String, String)>,
+}
+
+impl Request {
+    pub fn path(&self) -> &str {
+        self.target.split('?').next().unwrap_or_default()
+    }
+
+    pub fn query(&self) -> Option<&str> {
+        self.target.split_once('?').map(|(_, query)| query)
+    }
+
+    // Header names are case-insensitive
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn cookie(&self, name: &str) -> Option<&str> {
+        self.header("Cookie")?
+            .split(';')
+            .filter_map(|cookie| cookie.trim().split_once('='))
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value)
+    }
+}
+
+pub fn read_request(stream: &TcpStream) -> io::Result<Request> {
+    let malformed = || io::Error::new(io::ErrorKind::InvalidData, "malformed HTTP request");
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().ok_or_else(malformed)?.to_string();
+    let target = parts.next().ok_or_else(malformed)?.to_string();
+
+    // Headers run until the first blank line
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+        let (key, value) = line.split_once(':').ok_or_else(malformed)?;
+        headers.push((key.trim().to_string(), value.trim().to_string()));
+    }
+
+    Ok(Request {
+        method,
+        target,
+        headers,
+    })
+}
+
+pub fn write_response(
+    stream: &mut TcpStream,
+    status: u16,
+    headers: &[(&str, &str)],
+) -> io::Result<()> {
+    let mut response = format!("HTTP/1.1 {} {}\r\n", status, reason(status));
+    for (key, value) in headers {
+        response += &format!("{}: {}\r\n", key, value);
+    }
+    response += "Content-Length: 0\r\nConnection: close\r\n\r\n";
+
+    // One write, so the response isn't split into segments held back by Nagle's algorithm
+    stream.set_nodelay(true)?;
+    stream.write_all(response.as_bytes())
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        _ => "Unknown",
+    }
+}
+
+// Sends a GET to an http:// URL and returns the response status
+pub fn get(url: &str, headers: &[(&str, &str)]) -> io::Result<u16> {
+    let invalid_url =
+        || io::Error::new(io::ErrorKind::InvalidInput, format!("invalid URL: {}", url));
+
+    let rest = url.strip_prefix("http://").ok_or_else(invalid_url)?;
+    let (host, target) = match rest.find('/') {
+        Some(i) => rest.split_at(i),
+        None => (rest, "/"),
+    };
+    if host.is_empty() {
+        return Err(invalid_url());
+    }
+
+    let mut request = format!("GET {} HTTP/1.1\r\nHost: {}\r\n", target, host);
+    for (key, value) in headers {
+        request += &format!("{}: {}\r\n", key, value);
+    }
+    request += "Connection: close\r\n\r\n";
+
+    let addr = if host.contains(':') {
+        host.to_string()
+    } else {
+        format!("{}:80", host)
+    };
+    let mut stream = TcpStream::connect(addr)?;
+    stream.set_nodelay(true)?;
+    stream.write_all(request.as_bytes())?;
+
+    let mut status_line = String::new();
+    BufReader::new(stream).read_line(&mut status_line)?;
+    status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed HTTP response"))
+}
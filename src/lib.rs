@@ -0,0 +1,66 @@
+//! Solutions to the [Cryptopals challenges](https://cryptopals.com/), usable as a library.
+//!
+//! Encoding helpers live in [`util`]:
+//!
+//! ```
+//! use cryptopals::util;
+//!
+//! let bytes = util::hex_to_bytes("49276d")?;
+//! assert_eq!(util::bytes_to_base64(&bytes), "SSdt");
+//! assert_eq!(util::bytes_to_ascii(&util::base64_to_bytes("SSdt")?), "I'm");
+//!
+//! // Malformed input is reported rather than panicking
+//! assert_eq!(
+//!     util::hex_to_bytes("49z76d"),
+//!     Err(cryptopals::Error::InvalidHex { position: 2 })
+//! );
+//! # Ok::<(), cryptopals::Error>(())
+//! ```
+//!
+//! XOR ciphers and their attacks live in [`xor`]:
+//!
+//! ```
+//! use cryptopals::{util, xor};
+//!
+//! let ciphertext = xor::single_byte_xor(b"Cooking MC's like a pound of bacon", 88);
+//! let res = xor::decrypt_single_byte_xor(&ciphertext).unwrap();
+//! assert_eq!(res.key, 88);
+//! assert_eq!(util::bytes_to_ascii(&res.message), "Cooking MC's like a pound of bacon");
+//! ```
+//!
+//! AES modes live in [`aes`], with each attack in a submodule of the mode it targets:
+//!
+//! ```
+//! use cryptopals::aes;
+//!
+//! let key = b"YELLOW SUBMARINE";
+//! let iv = [0u8; 16];
+//! let ciphertext = aes::cbc::encrypt_aes_cbc(b"attack at dawn", key, &iv)?;
+//! assert_eq!(aes::cbc::decrypt_aes_cbc(&ciphertext, key, &iv)?, b"attack at dawn");
+//!
+//! let (ciphertext, iv, oracle) = aes::cbc::padding_oracle::gen_aes_cbc_padding_oracle(b"secret");
+//! let recovered = aes::cbc::padding_oracle::cbc_padding_oracle_attack(&ciphertext, &iv, &oracle)?;
+//! assert_eq!(aes::strip_pkcs7(&recovered)?, b"secret");
+//! # Ok::<(), cryptopals::Error>(())
+//! ```
+//!
+//! The Mersenne Twister lives in [`mt19937`]:
+//!
+//! ```
+//! use cryptopals::mt19937::MT19937;
+//!
+//! let mut rng = MT19937::new(5489);
+//! assert_eq!(rng.next(), Some(3499211612));
+//! ```
+
+pub mod aes;
+pub mod error;
+pub mod hash;
+pub(crate) mod http;
+pub mod mac;
+pub mod mt19937;
+pub mod oracle;
+pub mod util;
+pub mod xor;
+
+pub use error::Error;
@@ -0,0 +1,71 @@
+pub mod length_extension;
+pub mod timing_leak;
+
+use crate::hash::{md4::Md4, sha1::Sha1, HashFunction};
+
+/* ======== SECRET-PREFIX MAC ======== */
+// SHA1(key || message), which is vulnerable to length extension
+pub fn secret_prefix_sha1(key: &[u8], message: &[u8]) -> Vec<u8> {
+    secret_prefix::<Sha1>(key, message)
+}
+
+pub fn verify_secret_prefix_sha1(key: &[u8], message: &[u8], mac: &[u8]) -> bool {
+    secret_prefix_sha1(key, message) == mac
+}
+
+pub fn secret_prefix_md4(key: &[u8], message: &[u8]) -> Vec<u8> {
+    secret_prefix::<Md4>(key, message)
+}
+
+pub fn verify_secret_prefix_md4(key: &[u8], message: &[u8], mac: &[u8]) -> bool {
+    secret_prefix_md4(key, message) == mac
+}
+
+fn secret_prefix<H: HashFunction>(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut hasher = H::new();
+    hasher.update(key);
+    hasher.update(message);
+    hasher.finalize()
+}
+
+/* ======== HMAC (RFC 2104) ======== */
+/// HMAC over any of the crate's hash functions.
+///
+/// ```
+/// use cryptopals::{hash::sha1::Sha1, mac, util};
+///
+/// // RFC 2202 test cases 1, 2 and 6
+/// assert_eq!(
+///     util::bytes_to_hex(&mac::hmac::<Sha1>(&[0x0b; 20], b"Hi There")),
+///     "b617318655057264e28bc0b6fb378c8ef146be00"
+/// );
+/// assert_eq!(
+///     util::bytes_to_hex(&mac::hmac::<Sha1>(b"Jefe", b"what do ya want for nothing?")),
+///     "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79"
+/// );
+/// assert_eq!(
+///     util::bytes_to_hex(&mac::hmac::<Sha1>(
+///         &[0xaa; 80],
+///         b"Test Using Larger Than Block-Size Key - Hash Key First"
+///     )),
+///     "aa4ae5e15272d00e95705637ce8a3b55ed402112"
+/// );
+/// ```
+pub fn hmac<H: HashFunction>(key: &[u8], message: &[u8]) -> Vec<u8> {
+    // Keys longer than a block are hashed, then all keys are zero-padded to a block
+    let mut key = if key.len() > H::BLOCK_SIZE {
+        H::digest(key)
+    } else {
+        key.to_vec()
+    };
+    key.resize(H::BLOCK_SIZE, 0);
+
+    let mut inner = H::new();
+    inner.update(&key.iter().map(|b| b ^ 0x36).collect::<Vec<u8>>());
+    inner.update(message);
+
+    let mut outer = H::new();
+    outer.update(&key.iter().map(|b| b ^ 0x5c).collect::<Vec<u8>>());
+    outer.update(&inner.finalize());
+    outer.finalize()
+}
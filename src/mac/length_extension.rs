@@ -0,0 +1,147 @@
+use rand::{thread_rng, Rng};
+
+use crate::{
+    aes,
+    error::Error,
+    hash::{
+        md4::{self, Md4},
+        sha1::{self, Sha1},
+        HashFunction,
+    },
+    util,
+};
+
+/* ======== LENGTH EXTENSION ATTACK ======== */
+// Longest key length tried when guessing the key length
+const MAX_KEY_LEN: usize = 64;
+
+// A forged message and its MAC
+pub type Forgery = (Vec<u8>, Vec<u8>);
+
+pub fn sha1_length_extension_admin() -> Result<Option<Forgery>, Error> {
+    let (sign, verify) = gen_secret_prefix_oracles(super::secret_prefix_sha1);
+
+    let message = cooking_message();
+    let mac = sign(&message);
+
+    extend_secret_prefix_sha1(&message, &mac, b";admin=true", verify)
+}
+
+pub fn md4_length_extension_admin() -> Result<Option<Forgery>, Error> {
+    let (sign, verify) = gen_secret_prefix_oracles(super::secret_prefix_md4);
+
+    let message = cooking_message();
+    let mac = sign(&message);
+
+    extend_secret_prefix_md4(&message, &mac, b";admin=true", verify)
+}
+
+// Forges a MAC for message || glue padding || extension, without knowing the key
+// None if no key length up to MAX_KEY_LEN verifies, and an error if the MAC isn't a SHA-1 digest
+pub fn extend_secret_prefix_sha1(
+    message: &[u8],
+    mac: &[u8],
+    extension: &[u8],
+    verify: impl Fn(&[u8], &[u8]) -> bool,
+) -> Result<Option<Forgery>, Error> {
+    let state = Sha1::state_from_digest(mac)?;
+
+    Ok(extend_secret_prefix(
+        message,
+        extension,
+        verify,
+        sha1::padding,
+        |length| {
+            let mut sha1 = Sha1::from_state(state, length);
+            sha1.update(extension);
+            sha1.finalize()
+        },
+    ))
+}
+
+// Same as SHA-1, but the glue padding has a little-endian length
+pub fn extend_secret_prefix_md4(
+    message: &[u8],
+    mac: &[u8],
+    extension: &[u8],
+    verify: impl Fn(&[u8], &[u8]) -> bool,
+) -> Result<Option<Forgery>, Error> {
+    let state = Md4::state_from_digest(mac)?;
+
+    Ok(extend_secret_prefix(
+        message,
+        extension,
+        verify,
+        md4::padding,
+        |length| {
+            let mut md4 = Md4::from_state(state, length);
+            md4.update(extension);
+            md4.finalize()
+        },
+    ))
+}
+
+// Each plausible key length gives different glue padding, so they are tried until one verifies
+// resume hashes the extension from the MAC's state, given the length already processed
+fn extend_secret_prefix(
+    message: &[u8],
+    extension: &[u8],
+    verify: impl Fn(&[u8], &[u8]) -> bool,
+    padding: fn(u64) -> Vec<u8>,
+    resume: impl Fn(u64) -> Vec<u8>,
+) -> Option<Forgery> {
+    (0..=MAX_KEY_LEN).find_map(|key_len| {
+        let prefix_len = (key_len + message.len()) as u64;
+        let glue = padding(prefix_len);
+
+        let mut forged_message = message.to_vec();
+        forged_message.extend_from_slice(&glue);
+        forged_message.extend_from_slice(extension);
+
+        let forged_mac = resume(prefix_len + glue.len() as u64);
+
+        if verify(&forged_message, &forged_mac) {
+            Some((forged_message, forged_mac))
+        } else {
+            None
+        }
+    })
+}
+
+// The key has a random length, so the attack has to guess it
+#[allow(clippy::type_complexity)]
+pub fn gen_secret_prefix_oracles(
+    mac: fn(&[u8], &[u8]) -> Vec<u8>,
+) -> (impl Fn(&[u8]) -> Vec<u8>, impl Fn(&[u8], &[u8]) -> bool) {
+    let mut rng = thread_rng();
+    let key: Vec<u8> = (0..rng.gen_range(1..=32)).map(|_| rng.gen()).collect();
+    let key_verify = key.clone();
+
+    (
+        move |message: &[u8]| mac(&key, message),
+        move |message: &[u8], tag: &[u8]| mac(&key_verify, message) == tag,
+    )
+}
+
+// Whether any ;-separated field is admin=true, even the last, which a forged extension ends in
+// Unlike aes::cbc::bitflipping::is_admin, which needs a ; on both sides
+pub fn has_admin_field(bytes: &[u8]) -> bool {
+    bytes
+        .split(|&b| b == b';')
+        .any(|field| field == b"admin=true")
+}
+
+fn cooking_message() -> Vec<u8> {
+    util::ascii_to_bytes(&aes::encode_to_query_string(
+        &[
+            ("comment1".to_string(), "cooking%20MCs".to_string()),
+            ("userdata".to_string(), "foo".to_string()),
+            (
+                "comment2".to_string(),
+                "%20like%20a%20pound%20of%20bacon".to_string(),
+            ),
+        ],
+        ';',
+        '=',
+    ))
+}
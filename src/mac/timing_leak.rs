@@ -0,0 +1,306 @@
+use std::{
+    cmp::Reverse,
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use rand::{seq::SliceRandom, thread_rng};
+
+use crate::{aes, hash::HashFunction, http, util};
+
+/* ======== TIMING LEAK SERVER ======== */
+// Serves GET /test?file=...&signature=... on 127.0.0.1, on a random free port
+// 200 if the signature is HMAC(key, file), 500 if not, 400 for anything malformed
+// The server runs on a background thread until the process exits
+pub fn spawn_timing_leak_server<H: HashFunction>(
+    key: &[u8],
+    delay: Duration,
+) -> io::Result<SocketAddr> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+    let key = key.to_vec();
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let key = key.clone();
+            thread::spawn(move || handle_request::<H>(stream, &key, delay));
+        }
+    });
+
+    Ok(addr)
+}
+
+fn handle_request<H: HashFunction>(
+    mut stream: TcpStream,
+    key: &[u8],
+    delay: Duration,
+) -> io::Result<()> {
+    let status = match http::read_request(&stream) {
+        Ok(request) => match check_signature::<H>(&request, key, delay) {
+            Some(true) => 200,
+            Some(false) => 500,
+            None => 400,
+        },
+        Err(_) => 400,
+    };
+
+    http::write_response(&mut stream, status, &[])
+}
+
+// None if the request isn't a well-formed GET /test
+fn check_signature<H: HashFunction>(
+    request: &http::Request,
+    key: &[u8],
+    delay: Duration,
+) -> Option<bool> {
+    if request.method != "GET" || request.path() != "/test" {
+        return None;
+    }
+    let query = aes::parse_query_string(request.query()?).ok()?;
+
+    let find = |name: &str| {
+        query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+    let file = find("file")?;
+    let signature = util::hex_to_bytes(find("signature")?).ok()?;
+
+    let hmac = super::hmac::<H>(key, file.as_bytes());
+    Some(insecure_compare(&hmac, &signature, delay))
+}
+
+// Exits on the first mismatch, sleeping after every matching byte
+pub fn insecure_compare(a: &[u8], b: &[u8], delay: Duration) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    for (x, y) in a.iter().zip(b) {
+        if x != y {
+            return false;
+        }
+        thread::sleep(delay);
+    }
+
+    true
+}
+
+/* ======== TIMING ATTACK ======== */
+// Concurrent requests while timing, few enough that scheduling noise stays well under the delay
+const WORKERS: usize = 8;
+// Rounds of samples a byte may take before its best guess is accepted anyway
+const MAX_ROUNDS: usize = 8;
+
+// Median response times of the slowest and second slowest guesses for a byte
+#[derive(Clone, Copy, Debug)]
+pub struct ByteTiming {
+    pub byte: u8,
+    pub best: Duration,
+    pub runner_up: Duration,
+}
+
+// Recovers the signature for file one byte at a time
+// The right guess for each byte takes one delay longer than the rest to be rejected, so the
+// typical (wrong) guess also gets one delay slower with every right byte before it
+// Bytes that don't stand out by half a delay are sampled again, and a byte that doesn't raise
+// the typical time means the one before it was wrong, so that one is done over
+// More samples per guess are needed as the delay shrinks towards the noise
+pub fn recover_hmac_by_timing(
+    addr: SocketAddr,
+    file: &str,
+    mac_len: usize,
+    samples: usize,
+) -> io::Result<(Vec<u8>, Vec<ByteTiming>)> {
+    if samples == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "at least one sample per guess is needed",
+        ));
+    }
+
+    let mut signature = vec![0u8; mac_len];
+    let mut timings: Vec<ByteTiming> = Vec::with_capacity(mac_len);
+    // Median time of all guesses for each byte so far
+    let mut levels: Vec<Duration> = Vec::with_capacity(mac_len);
+    let mut backtracks = 0;
+
+    while timings.len() < mac_len {
+        let i = timings.len();
+
+        // The delay, as far as the bytes so far show it
+        let mut steps: Vec<Duration> = timings
+            .iter()
+            .zip(&levels)
+            .map(|(timing, &level)| timing.best.saturating_sub(level))
+            .collect();
+        steps.sort();
+        let step = steps.get(steps.len() / 2).copied();
+
+        let (timing, level, valid) = time_byte(addr, file, &signature, i, samples, step)?;
+
+        // No slower than the last byte, or no accepted guess at the end, so the last byte is wrong
+        let stalled = match (step, levels.last()) {
+            (Some(step), Some(&prev_level)) => level < prev_level + step / 2,
+            _ => false,
+        };
+        if (stalled || (i == mac_len - 1 && !valid)) && i > 0 && backtracks < mac_len {
+            backtracks += 1;
+            timings.pop();
+            levels.pop();
+            continue;
+        }
+
+        signature[i] = timing.byte;
+        timings.push(timing);
+        levels.push(level);
+    }
+
+    Ok((signature, timings))
+}
+
+// Times every value for the byte at index i, a round of samples at a time, until the best guess
+// is accepted, or stands out from the rest by half a step (or, before any step is known, by more
+// than the rest are spread)
+// Returns the timing, the median time of all guesses, and whether the best guess was accepted
+fn time_byte(
+    addr: SocketAddr,
+    file: &str,
+    signature: &[u8],
+    i: usize,
+    samples: usize,
+    step: Option<Duration>,
+) -> io::Result<(ByteTiming, Duration, bool)> {
+    let mut times = vec![Vec::new(); 256];
+    let mut valid = [false; 256];
+
+    let mut rounds = 0;
+    loop {
+        for _ in 0..samples {
+            for (byte, time, accepted) in time_guesses(addr, file, signature, i)? {
+                times[byte as usize].push(time);
+                valid[byte as usize] |= accepted;
+            }
+        }
+        rounds += 1;
+
+        let mut medians: Vec<(u8, Duration)> = times
+            .iter_mut()
+            .enumerate()
+            .map(|(byte, times)| {
+                times.sort();
+                (byte as u8, times[times.len() / 2])
+            })
+            .collect();
+
+        // A guess that is accepted outright must be right, however long it took
+        medians.sort_by_key(|&(byte, time)| Reverse((valid[byte as usize], time)));
+        let (byte, best) = medians[0];
+        let runner_up = medians[1].1;
+        let level = medians[medians.len() / 2].1;
+
+        let margin = best.saturating_sub(runner_up);
+        let threshold = step.map_or(runner_up.saturating_sub(level), |step| step / 2);
+
+        // After MAX_ROUNDS the best guess is taken as it stands
+        if valid[byte as usize] || margin > threshold || rounds == MAX_ROUNDS {
+            let timing = ByteTiming {
+                byte,
+                best,
+                runner_up,
+            };
+            return Ok((timing, level, valid[byte as usize]));
+        }
+    }
+}
+
+// Tries every value for the byte at index i once, returning the time and result of each
+// The guesses go in a random order, so noise that comes and goes is spread over all of them
+fn time_guesses(
+    addr: SocketAddr,
+    file: &str,
+    signature: &[u8],
+    i: usize,
+) -> io::Result<Vec<(u8, Duration, bool)>> {
+    let mut order: Vec<u8> = (0..=u8::MAX).collect();
+    order.shuffle(&mut thread_rng());
+    let next = AtomicUsize::new(0);
+
+    thread::scope(|s| {
+        let workers: Vec<_> = (0..WORKERS)
+            .map(|_| {
+                let (order, next) = (&order, &next);
+                s.spawn(move || {
+                    let mut guesses = Vec::new();
+                    while let Some(&byte) = order.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        let mut guess = signature.to_vec();
+                        guess[i] = byte;
+
+                        let (accepted, time) = request_signature(addr, file, &guess)?;
+                        guesses.push((byte, time, accepted));
+                    }
+                    Ok::<_, io::Error>(guesses)
+                })
+            })
+            .collect();
+
+        let mut guesses = Vec::with_capacity(256);
+        for worker in workers {
+            let worker_guesses = worker
+                .join()
+                .map_err(|_| io::Error::other("timing worker panicked"))??;
+            guesses.extend(worker_guesses);
+        }
+        Ok(guesses)
+    })
+}
+
+// Returns whether the server accepted the signature, and how long it took to answer
+// Anything but a 200 or a 500 is an error, rather than a rejection to be timed
+pub fn request_signature(
+    addr: SocketAddr,
+    file: &str,
+    signature: &[u8],
+) -> io::Result<(bool, Duration)> {
+    let request = format!(
+        "GET /test?file={}&signature={} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        percent_encode(file),
+        util::bytes_to_hex(signature),
+        addr
+    );
+
+    let mut stream = TcpStream::connect(addr)?;
+    stream.set_nodelay(true)?;
+    let start = Instant::now();
+    stream.write_all(request.as_bytes())?;
+
+    let mut status_line = String::new();
+    BufReader::new(stream).read_line(&mut status_line)?;
+    let elapsed = start.elapsed();
+
+    match status_line.split_whitespace().nth(1) {
+        Some("200") => Ok((true, elapsed)),
+        Some("500") => Ok((false, elapsed)),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unexpected response: {}", status_line.trim_end()),
+        )),
+    }
+}
+
+// Escapes everything but unreserved characters, so any file name survives the query string
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
@@ -1,15 +1,83 @@
-#![allow(dead_code)]
+use std::{env, error::Error, fs, process};
 
-use std::error::Error;
+mod challenges;
 
-mod aes;
-mod mt19937;
-mod util;
-mod xor;
+const USAGE: &str = "Usage:
+    cryptopals list
+    cryptopals run <set>/<challenge> [--input <file>]";
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let mut rng = mt19937::MT19937::new(5489);
-    println!("{}", rng.nth(9999).unwrap());
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let res = match args.first().map(String::as_str) {
+        Some("list") => list(),
+        Some("run") => run(&args[1..]),
+        _ => Err(USAGE.into()),
+    };
+
+    if let Err(err) = res {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
+
+fn list() -> Result<(), Box<dyn Error>> {
+    for challenge in challenges::CHALLENGES {
+        println!(
+            "{:>5}  {}",
+            format!("{}/{}", challenge.set, challenge.number),
+            challenge.title
+        );
+    }
+
+    Ok(())
+}
+
+fn run(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut id = None;
+    let mut input_path = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => input_path = Some(args.next().ok_or("Missing file after --input")?),
+            _ if id.is_none() => id = Some(arg),
+            _ => return Err(format!("Unexpected argument: {}\n{}", arg, USAGE).into()),
+        }
+    }
+
+    let id = id.ok_or(USAGE)?;
+    let (set, number) = id
+        .split_once('/')
+        .ok_or_else(|| format!("Expected <set>/<challenge>, got {}", id))?;
+    let (set, number): (u8, u8) = (set.parse()?, number.parse()?);
+
+    let challenge = challenges::find_challenge(set, number)
+        .ok_or_else(|| format!("No challenge {} in set {}", number, set))?;
+
+    // Fall back to the challenge's file in assets/ if no input was given
+    let input = match (input_path, challenge.asset) {
+        (Some(path), _) => Some(fs::read_to_string(path)?),
+        (None, Some(asset)) => Some(fs::read_to_string(
+            [env!("CARGO_MANIFEST_DIR"), "assets", asset].join("/"),
+        )?),
+        (None, None) => None,
+    };
+
+    let report = (challenge.run)(input.as_deref())?;
+
+    println!(
+        "Set {} / Challenge {}: {}",
+        challenge.set, challenge.number, challenge.title
+    );
+    let width = report.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+    for (key, value) in report {
+        // Indent multi-line values so they line up under the first line
+        let value = value
+            .trim_end()
+            .replace('\n', &format!("\n  {:width$}   ", ""));
+        println!("  {:width$} : {}", key, value);
+    }
 
     Ok(())
 }
@@ -1,71 +1,581 @@
+pub mod jump;
+pub mod stream;
+pub mod time_seed;
+
+use std::{
+    fmt::Debug,
+    ops::{BitAnd, BitOr, BitXor, Not, Shl, Shr},
+};
+
+use rand::{RngCore, SeedableRng};
+
+use crate::error::Error;
+
+/* ======== PARAMETER SETS ======== */
+// Unsigned integers the generator can work on, where W is the full width of the word
+pub trait Word:
+    Copy
+    + Debug
+    + Eq
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + BitXor<Output = Self>
+    + Not<Output = Self>
+    + Shl<usize, Output = Self>
+    + Shr<usize, Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn wrapping_add(self, rhs: Self) -> Self;
+
+    fn wrapping_sub(self, rhs: Self) -> Self;
+
+    fn wrapping_mul(self, rhs: Self) -> Self;
+
+    fn from_usize(n: usize) -> Self;
+}
+
+macro_rules! impl_word {
+    ($($t:ty),*) => {
+        $(
+            impl Word for $t {
+                const ZERO: $t = 0;
+                const ONE: $t = 1;
+
+                fn wrapping_add(self, rhs: $t) -> $t {
+                    <$t>::wrapping_add(self, rhs)
+                }
+
+                fn wrapping_sub(self, rhs: $t) -> $t {
+                    <$t>::wrapping_sub(self, rhs)
+                }
+
+                fn wrapping_mul(self, rhs: $t) -> $t {
+                    <$t>::wrapping_mul(self, rhs)
+                }
+
+                fn from_usize(n: usize) -> $t {
+                    n as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_word!(u32, u64);
+
+// The constants of a Mersenne Twister, named as in the paper
+// State should be an array of N words
+pub trait MtParams: 'static {
+    type Word: Word;
+    type State: Copy + AsRef<[Self::Word]> + AsMut<[Self::Word]>;
+
+    const ZERO_STATE: Self::State;
+
+    const W: usize;
+    const N: usize;
+    const M: usize;
+    const R: usize;
+    const A: Self::Word;
+    const U: usize;
+    const D: Self::Word;
+    const S: usize;
+    const B: Self::Word;
+    const T: usize;
+    const C: Self::Word;
+    const L: usize;
+    const F: Self::Word;
+}
+
+pub struct Mt19937Params;
+
+impl MtParams for Mt19937Params {
+    type Word = u32;
+    type State = [u32; 624];
+
+    const ZERO_STATE: [u32; 624] = [0; 624];
+
+    const W: usize = 32;
+    const N: usize = 624;
+    const M: usize = 397;
+    const R: usize = 31;
+    const A: u32 = 0x9908B0DF;
+    const U: usize = 11;
+    const D: u32 = 0xFFFFFFFF;
+    const S: usize = 7;
+    const B: u32 = 0x9D2C5680;
+    const T: usize = 15;
+    const C: u32 = 0xEFC60000;
+    const L: usize = 18;
+    const F: u32 = 1812433253;
+}
+
+#[allow(non_camel_case_types)]
+pub struct Mt19937_64Params;
+
+impl MtParams for Mt19937_64Params {
+    type Word = u64;
+    type State = [u64; 312];
+
+    const ZERO_STATE: [u64; 312] = [0; 312];
+
+    const W: usize = 64;
+    const N: usize = 312;
+    const M: usize = 156;
+    const R: usize = 31;
+    const A: u64 = 0xB5026F5AA96619E9;
+    const U: usize = 29;
+    const D: u64 = 0x5555555555555555;
+    const S: usize = 17;
+    const B: u64 = 0x71D67FFFEDA60000;
+    const T: usize = 37;
+    const C: u64 = 0xFFF7EEE000000000;
+    const L: usize = 43;
+    const F: u64 = 6364136223846793005;
+}
+
+// The smaller generator from the original paper, as in Boost's mt11213b
+pub struct Mt11213bParams;
+
+impl MtParams for Mt11213bParams {
+    type Word = u32;
+    type State = [u32; 351];
+
+    const ZERO_STATE: [u32; 351] = [0; 351];
+
+    const W: usize = 32;
+    const N: usize = 351;
+    const M: usize = 175;
+    const R: usize = 19;
+    const A: u32 = 0xCCAB8EE7;
+    const U: usize = 11;
+    const D: u32 = 0xFFFFFFFF;
+    const S: usize = 7;
+    const B: u32 = 0x31B6AB00;
+    const T: usize = 15;
+    const C: u32 = 0xFFE50000;
+    const L: usize = 17;
+    const F: u32 = 1812433253;
+}
+
+pub type MT19937 = MersenneTwister<Mt19937Params>;
+#[allow(non_camel_case_types)]
+pub type MT19937_64 = MersenneTwister<Mt19937_64Params>;
+pub type MT11213B = MersenneTwister<Mt11213bParams>;
+
 /* ======== MERSENNE TWISTER ======== */
-const W: usize = 32;
-const N: usize = 624;
-const M: usize = 397;
-const R: usize = 31;
-const A: u32 = 0x9908B0DF;
-const U: usize = 11;
-const D: u32 = 0xFFFFFFFF;
-const S: usize = 7;
-const B: u32 = 0x9D2C5680;
-const T: usize = 15;
-const C: u32 = 0xEFC60000;
-const L: usize = 18;
-const F: u32 = 1812433253;
-
-const LOWER_MASK: u32 = (1 << R) - 1;
-const UPPER_MASK: u32 = !LOWER_MASK;
-
-pub struct MT19937 {
-    state: [u32; N],
+// Outputs from which discarding jumps rather than twists, around where the two take as long
+const JUMP_THRESHOLD: u64 = 1 << 23;
+
+/// A Mersenne Twister with any parameter set.
+///
+/// ```
+/// use cryptopals::mt19937::{MT11213B, MT19937_64};
+///
+/// // std::mt19937_64 and boost::mt11213b, default seeded
+/// assert_eq!(MT19937_64::new(5489).nth(9999), Some(9981545732273789042));
+/// assert_eq!(MT11213B::new(5489).nth(9999), Some(3809585648));
+///
+/// // mt19937-64.out from the reference implementation
+/// let rng = MT19937_64::init_by_array(&[0x12345, 0x23456, 0x34567, 0x45678]);
+/// assert!(rng.take(5).eq([
+///     7266447313870364031,
+///     4946485549665804864,
+///     16945909448695747420,
+///     16394063075524226720,
+///     4873882236456199058,
+/// ]));
+/// ```
+pub struct MersenneTwister<P: MtParams> {
+    state: P::State,
     index: usize,
 }
 
-impl MT19937 {
-    pub fn new(seed: u32) -> MT19937 {
-        let mut state = [seed.into(); N];
-        for i in 1..N {
-            state[i] = F
-                .wrapping_mul(state[i - 1] ^ (state[i - 1] >> (W - 2)))
-                .wrapping_add(i as u32);
+// Derive would needlessly require the parameters to be Clone
+impl<P: MtParams> Clone for MersenneTwister<P> {
+    fn clone(&self) -> MersenneTwister<P> {
+        MersenneTwister {
+            state: self.state,
+            index: self.index,
         }
+    }
+}
 
-        MT19937 { state, index: N }
+impl<P: MtParams> MersenneTwister<P> {
+    pub fn new(seed: P::Word) -> MersenneTwister<P> {
+        let mut state = P::ZERO_STATE;
+        let words = state.as_mut();
+
+        words[0] = seed;
+        for i in 1..P::N {
+            words[i] = P::F
+                .wrapping_mul(words[i - 1] ^ (words[i - 1] >> (P::W - 2)))
+                .wrapping_add(P::Word::from_usize(i));
+        }
+
+        MersenneTwister { state, index: P::N }
     }
 
-    fn extract_number(&mut self) -> u32 {
-        if self.index >= N {
-            self.twist();
+    /// Index is the position of the next state word to be tempered and output.
+    /// An index of N means the state is twisted before the next output.
+    ///
+    /// ```
+    /// use cryptopals::{mt19937::{self, MT19937}, Error};
+    ///
+    /// let mut rng = MT19937::new(5489);
+    /// let mut state = [0u32; 624];
+    /// for word in &mut state {
+    ///     *word = mt19937::untemper(rng.next().unwrap());
+    /// }
+    ///
+    /// let mut clone = MT19937::from_state(state, 624)?;
+    /// assert_eq!(clone.next(), rng.next());
+    ///
+    /// assert_eq!(
+    ///     MT19937::from_state(state, 625).err(),
+    ///     Some(Error::InvalidStateIndex { index: 625 })
+    /// );
+    /// # Ok::<(), cryptopals::Error>(())
+    /// ```
+    pub fn from_state(state: P::State, index: usize) -> Result<MersenneTwister<P>, Error> {
+        if index > P::N {
+            return Err(Error::InvalidStateIndex { index });
         }
 
-        let mut y = self.state[self.index];
-        y ^= (y >> U) & D;
-        y ^= (y << S) & B;
-        y ^= (y << T) & C;
-        y ^= y >> L;
+        Ok(MersenneTwister { state, index })
+    }
+
+    fn extract_number(&mut self) -> P::Word {
+        if self.index >= P::N {
+            self.twist();
+        }
 
+        let y = Self::temper(self.state.as_ref()[self.index]);
         self.index += 1;
 
         y
     }
 
-    fn twist(&mut self) {
-        for i in 0..N {
-            let x = (self.state[i] & UPPER_MASK) | (self.state[(i + 1) % N] & LOWER_MASK);
-            let mut x_a = x >> 1;
-            if x % 2 != 0 {
-                x_a ^= A;
+    // Skips n outputs without tempering them, only twisting once per block of N
+    // Far enough ahead, jumping is cheaper than twisting through every block in between
+    pub fn discard(&mut self, mut n: u64) {
+        if n >= JUMP_THRESHOLD {
+            self.jump(&jump::JumpPolynomial::new(n as u128));
+            return;
+        }
+
+        while n > 0 {
+            if self.index >= P::N {
+                self.twist();
             }
-            self.state[i] = self.state[(i + M) % N] ^ x_a;
+
+            let skip = n.min((P::N - self.index) as u64);
+            self.index += skip as usize;
+            n -= skip;
+        }
+    }
+
+    fn twist(&mut self) {
+        let state = self.state.as_mut();
+        for i in 0..P::N {
+            Self::twist_word(state, i);
         }
         self.index = 0;
     }
+
+    // Replaces the word at i with the one N words after it in the sequence
+    fn twist_word(state: &mut [P::Word], i: usize) {
+        let lower_mask = !(!P::Word::ZERO << P::R);
+        let upper_mask = !lower_mask;
+
+        let x = (state[i] & upper_mask) | (state[(i + 1) % P::N] & lower_mask);
+        let mut x_a = x >> 1;
+        if x & P::Word::ONE != P::Word::ZERO {
+            x_a = x_a ^ P::A;
+        }
+        state[i] = state[(i + P::M) % P::N] ^ x_a;
+    }
+
+    fn temper(mut y: P::Word) -> P::Word {
+        y = y ^ ((y >> P::U) & P::D);
+        y = y ^ ((y << P::S) & P::B);
+        y = y ^ ((y << P::T) & P::C);
+        y = y ^ (y >> P::L);
+        y
+    }
+
+    // Shared by the reference init_by_array of each word size, which only differ in multipliers
+    fn init_by_array_with(key: &[P::Word], mul1: P::Word, mul2: P::Word) -> MersenneTwister<P> {
+        assert!(!key.is_empty(), "init_by_array needs at least one word");
+
+        let mut mt: MersenneTwister<P> = MersenneTwister::new(P::Word::from_usize(19650218));
+        let state = mt.state.as_mut();
+        let mix = |prev: P::Word| prev ^ (prev >> (P::W - 2));
+
+        let (mut i, mut j) = (1, 0);
+        for _ in 0..P::N.max(key.len()) {
+            state[i] = (state[i] ^ mix(state[i - 1]).wrapping_mul(mul1))
+                .wrapping_add(key[j])
+                .wrapping_add(P::Word::from_usize(j));
+            i += 1;
+            j += 1;
+            if i >= P::N {
+                state[0] = state[P::N - 1];
+                i = 1;
+            }
+            if j >= key.len() {
+                j = 0;
+            }
+        }
+        for _ in 0..P::N - 1 {
+            state[i] = (state[i] ^ mix(state[i - 1]).wrapping_mul(mul2))
+                .wrapping_sub(P::Word::from_usize(i));
+            i += 1;
+            if i >= P::N {
+                state[0] = state[P::N - 1];
+                i = 1;
+            }
+        }
+
+        // Makes sure the state isn't all zeros
+        state[0] = P::Word::ONE << (P::W - 1);
+
+        mt
+    }
+}
+
+impl MT19937 {
+    /// Seeds from an array of words, as `init_by_array` does in the reference implementation.
+    ///
+    /// ```
+    /// use cryptopals::mt19937::MT19937;
+    ///
+    /// // mt19937ar.out from the reference implementation
+    /// let rng = MT19937::init_by_array(&[0x123, 0x234, 0x345, 0x456]);
+    /// assert!(rng.take(5).eq([1067595299, 955945823, 477289528, 4107218783, 4228976476]));
+    /// ```
+    pub fn init_by_array(key: &[u32]) -> MT19937 {
+        MT19937::init_by_array_with(key, 1664525, 1566083941)
+    }
+}
+
+impl MT19937_64 {
+    // init_by_array64 from the 64-bit reference implementation
+    pub fn init_by_array(key: &[u64]) -> MT19937_64 {
+        MT19937_64::init_by_array_with(key, 3935559000370003845, 2862933555777941757)
+    }
 }
 
-impl Iterator for MT19937 {
-    type Item = u32;
+/* ======== CLONING ======== */
+impl<P: MtParams> MersenneTwister<P> {
+    /// Inverts the tempering applied to each output, recovering the state word behind it.
+    pub fn untemper(mut y: P::Word) -> P::Word {
+        y = undo_right_shift_xor::<P>(y, P::L, !P::Word::ZERO);
+        y = undo_left_shift_xor::<P>(y, P::T, P::C);
+        y = undo_left_shift_xor::<P>(y, P::S, P::B);
+        y = undo_right_shift_xor::<P>(y, P::U, P::D);
+        y
+    }
+
+    /// Rebuilds a generator from any N consecutive outputs.
+    /// The clone's outputs are the ones that follow them. Returns `None` if there are fewer outputs.
+    ///
+    /// ```
+    /// use cryptopals::mt19937::{MT11213B, MT19937_64};
+    ///
+    /// let mut rng = MT19937_64::new(1234);
+    /// let mut clone = MT19937_64::clone_from_outputs(rng.by_ref().skip(100).take(312)).unwrap();
+    /// assert!(rng.take(10000).eq(clone.take(10000)));
+    ///
+    /// let mut rng = MT11213B::new(1234);
+    /// let mut clone = MT11213B::clone_from_outputs(rng.by_ref().take(351)).unwrap();
+    /// assert!(rng.take(10000).eq(clone.by_ref().take(10000)));
+    /// assert!(MT11213B::clone_from_outputs(clone.take(350)).is_none());
+    /// ```
+    pub fn clone_from_outputs(
+        outputs: impl Iterator<Item = P::Word>,
+    ) -> Option<MersenneTwister<P>> {
+        let mut state = P::ZERO_STATE;
+        let mut len = 0;
+        for (word, output) in state.as_mut().iter_mut().zip(outputs) {
+            *word = Self::untemper(output);
+            len += 1;
+        }
+
+        if len < P::N {
+            return None;
+        }
+
+        Some(MersenneTwister { state, index: P::N })
+    }
+}
+
+/// Inverts the tempering applied to each MT19937 output, recovering the state word behind it.
+pub fn untemper(y: u32) -> u32 {
+    MT19937::untemper(y)
+}
+
+/// Rebuilds an MT19937 from any 624 consecutive outputs.
+/// The clone's outputs are the ones that follow them. Returns `None` if there are fewer outputs.
+///
+/// ```
+/// use cryptopals::mt19937::{self, MT19937};
+///
+/// let mut rng = MT19937::new(1234);
+/// let mut clone = mt19937::clone_from_outputs(rng.by_ref().take(624)).unwrap();
+///
+/// assert!(rng.by_ref().take(10000).eq(clone.by_ref().take(10000)));
+///
+/// // The outputs don't need to line up with a twist
+/// let mut clone = mt19937::clone_from_outputs(rng.by_ref().skip(100).take(624)).unwrap();
+/// assert!(rng.take(10000).eq(clone.by_ref().take(10000)));
+/// assert!(mt19937::clone_from_outputs(clone.take(623)).is_none());
+/// ```
+pub fn clone_from_outputs(outputs: impl Iterator<Item = u32>) -> Option<MT19937> {
+    MT19937::clone_from_outputs(outputs)
+}
+
+// Undoes y ^= (y >> shift) & mask, a few known bits at a time from the top
+fn undo_right_shift_xor<P: MtParams>(y: P::Word, shift: usize, mask: P::Word) -> P::Word {
+    let mut x = y;
+    for _ in 0..P::W / shift {
+        x = y ^ ((x >> shift) & mask);
+    }
+    x
+}
+
+// Undoes y ^= (y << shift) & mask, a few known bits at a time from the bottom
+fn undo_left_shift_xor<P: MtParams>(y: P::Word, shift: usize, mask: P::Word) -> P::Word {
+    let mut x = y;
+    for _ in 0..P::W / shift {
+        x = y ^ ((x << shift) & mask);
+    }
+    x
+}
+
+impl<P: MtParams> Iterator for MersenneTwister<P> {
+    type Item = P::Word;
 
     fn next(&mut self) -> Option<Self::Item> {
         Some(self.extract_number())
     }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.discard(n as u64);
+        self.next()
+    }
+}
+
+/* ======== RAND INTEGRATION ======== */
+/// Outputs are used as they come from `genrand_int32`, so this works anywhere `rand` expects an RNG.
+///
+/// ```
+/// use cryptopals::mt19937::MT19937;
+/// use rand::{Rng, RngCore, SeedableRng};
+///
+/// let mut rng = MT19937::seed_from_u64(5489);
+/// assert_eq!(rng.next_u32(), 3499211612);
+///
+/// // Bytes come from each output in turn, least significant first
+/// let mut bytes = [0; 6];
+/// rng.fill_bytes(&mut bytes);
+/// let mut reference = MT19937::new(5489).skip(1);
+/// let expected = [reference.next().unwrap().to_le_bytes(), reference.next().unwrap().to_le_bytes()];
+/// assert_eq!(bytes, expected.concat()[..6]);
+///
+/// // Python's random module seeds with init_by_array, and also puts the first output in the low word
+/// let mut rng = MT19937::init_by_array(&[5489]);
+/// assert_eq!(rng.next_u32(), 3382763572);
+/// assert_eq!(rng.next_u64(), 1794268081153815071);
+///
+/// // Anything generic over rand's RNGs is reproducible with a fixed seed
+/// let key: [u8; 16] = MT19937::seed_from_u64(1).gen();
+/// assert_eq!(key, MT19937::seed_from_u64(1).gen::<[u8; 16]>());
+/// ```
+impl RngCore for MT19937 {
+    fn next_u32(&mut self) -> u32 {
+        self.extract_number()
+    }
+
+    // The first output is the low word, as with rand's other 32-bit generators
+    fn next_u64(&mut self) -> u64 {
+        let low = self.extract_number() as u64;
+        let high = self.extract_number() as u64;
+
+        (high << 32) | low
+    }
+
+    // Unused bytes from the last output are dropped
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(4) {
+            let bytes = self.extract_number().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for MT19937 {
+    type Seed = [u8; 4];
+
+    fn from_seed(seed: Self::Seed) -> MT19937 {
+        MT19937::new(u32::from_le_bytes(seed))
+    }
+
+    // The default mixes the seed up first, this keeps it the same as new() for 32-bit seeds
+    fn seed_from_u64(state: u64) -> MT19937 {
+        MT19937::new(state as u32)
+    }
+}
+
+/// Outputs are used as they come from `genrand64_int64`.
+///
+/// ```
+/// use cryptopals::mt19937::MT19937_64;
+/// use rand::{RngCore, SeedableRng};
+///
+/// let mut rng = MT19937_64::seed_from_u64(5489);
+/// assert_eq!(rng.next_u64(), 14514284786278117030);
+///
+/// // Smaller requests take the low bits
+/// assert_eq!(rng.next_u32(), 4620546740167642908u64 as u32);
+/// ```
+impl RngCore for MT19937_64 {
+    fn next_u32(&mut self) -> u32 {
+        self.extract_number() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.extract_number()
+    }
+
+    // Unused bytes from the last output are dropped
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.extract_number().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for MT19937_64 {
+    type Seed = [u8; 8];
+
+    fn from_seed(seed: Self::Seed) -> MT19937_64 {
+        MT19937_64::new(u64::from_le_bytes(seed))
+    }
+
+    fn seed_from_u64(state: u64) -> MT19937_64 {
+        MT19937_64::new(state)
+    }
 }
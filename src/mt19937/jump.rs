@@ -0,0 +1,318 @@
+use std::{
+    any::TypeId,
+    collections::HashMap,
+    marker::PhantomData,
+    sync::{Mutex, OnceLock},
+};
+
+use super::{MersenneTwister, MtParams, Word};
+
+/* ======== JUMP AHEAD ======== */
+// The twist is linear over GF(2), so stepping the state J times is multiplying it by A^J.
+// With φ the characteristic polynomial of A, A^J = p(A) where p(x) = x^J mod φ(x),
+// which only needs about log J squarings of polynomials of degree below φ's.
+
+/// A precomputed jump of a fixed number of steps, reusable for any generator with the same parameters.
+///
+/// ```
+/// use cryptopals::mt19937::{jump::JumpPolynomial, MT19937};
+///
+/// // Parallel streams, each 2^20 outputs after the one before
+/// let jump = JumpPolynomial::new(1 << 20);
+/// let mut streams = vec![MT19937::new(5489)];
+/// for _ in 1..3 {
+///     let mut next = streams.last().unwrap().clone();
+///     next.jump(&jump);
+///     streams.push(next);
+/// }
+///
+/// let mut rng = MT19937::new(5489);
+/// for stream in streams.iter_mut().skip(1) {
+///     rng.discard(1 << 20);
+///     assert_eq!(stream.next(), rng.clone().next());
+/// }
+///
+/// // Jumping works from part way through a block too
+/// let (mut jumped, mut stepped) = (MT19937::new(1), MT19937::new(1));
+/// jumped.discard(100);
+/// jumped.jump_ahead(16);
+/// stepped.discard(100 + (1 << 16));
+/// assert!(jumped.take(1000).eq(stepped.take(1000)));
+///
+/// // Long discards jump too, landing where stepping would
+/// let (mut far, mut near) = (MT19937::new(2), MT19937::new(2));
+/// far.discard((1 << 23) + 5);
+/// near.discard(1 << 22);
+/// near.discard(1 << 22);
+/// near.discard(5);
+/// assert_eq!(far.next(), near.next());
+///
+/// // So offsets far beyond any stepping are in reach
+/// far.discard(1 << 40);
+/// ```
+pub struct JumpPolynomial<P: MtParams> {
+    // Coefficients of x^(steps - 1) mod φ, lowest degree first
+    coeffs: Vec<u64>,
+    params: PhantomData<P>,
+}
+
+impl<P: MtParams> JumpPolynomial<P> {
+    pub fn new(steps: u128) -> JumpPolynomial<P> {
+        assert!(steps > 0, "a jump must be at least one step");
+
+        let modulus = modulus::<P>();
+
+        // Square and multiply from the top bit of the exponent down
+        let exponent = steps - 1;
+        let mut coeffs = vec![0u64; modulus.words];
+        coeffs[0] = 1;
+        for bit in (0..128 - exponent.leading_zeros()).rev() {
+            coeffs = modulus.square(&coeffs);
+            if (exponent >> bit) & 1 == 1 {
+                modulus.mul_x(&mut coeffs);
+            }
+        }
+
+        JumpPolynomial {
+            coeffs,
+            params: PhantomData,
+        }
+    }
+}
+
+impl<P: MtParams> MersenneTwister<P> {
+    // Skips 2^k outputs, computing the jump polynomial each time
+    pub fn jump_ahead(&mut self, k: u32) {
+        assert!(k < 128, "can't jump 2^{} steps", k);
+
+        self.jump(&JumpPolynomial::new(1 << k));
+    }
+
+    // The index is kept, so the outputs carry on from the same place in the jumped state
+    pub fn jump(&mut self, jump: &JumpPolynomial<P>) {
+        // The lower bits of the first word never affect later words, so φ(A) only vanishes on
+        // states that have been stepped at least once. Step once here, and jump one fewer.
+        let mut stepped = Window::<P>::new(self.state);
+        stepped.step();
+
+        // Horner's method, where multiplying by x is stepping the state
+        let mut jumped = Window::<P>::new(P::ZERO_STATE);
+        for i in (0..jump.coeffs.len() * 64).rev() {
+            jumped.step();
+            if bit(&jump.coeffs, i) {
+                jumped.add(&stepped);
+            }
+        }
+
+        self.state = jumped.into_state();
+    }
+}
+
+// A state as a circular buffer, so stepping only replaces one word
+struct Window<P: MtParams> {
+    words: P::State,
+    start: usize,
+}
+
+impl<P: MtParams> Window<P> {
+    fn new(words: P::State) -> Window<P> {
+        Window { words, start: 0 }
+    }
+
+    fn step(&mut self) {
+        MersenneTwister::<P>::twist_word(self.words.as_mut(), self.start);
+        self.start = (self.start + 1) % P::N;
+    }
+
+    fn add(&mut self, other: &Window<P>) {
+        let (words, other_words) = (self.words.as_mut(), other.words.as_ref());
+        for i in 0..P::N {
+            let j = (self.start + i) % P::N;
+            words[j] = words[j] ^ other_words[(other.start + i) % P::N];
+        }
+    }
+
+    fn into_state(mut self) -> P::State {
+        self.words.as_mut().rotate_left(self.start);
+        self.words
+    }
+}
+
+/* ======== GF(2) POLYNOMIALS ======== */
+// Polynomials are bit vectors, with the coefficient of x^i at bit i
+
+// φ for each parameter set, found once per process and kept for every later jump
+fn modulus<P: MtParams>() -> &'static Modulus {
+    static MODULI: OnceLock<Mutex<HashMap<TypeId, &'static Modulus>>> = OnceLock::new();
+
+    let mut moduli = MODULI.get_or_init(Default::default).lock().unwrap();
+    moduli
+        .entry(TypeId::of::<P>())
+        .or_insert_with(|| Box::leak(Box::new(Modulus::new(characteristic_polynomial::<P>()))))
+}
+
+// Berlekamp-Massey on one bit of each word of the sequence, which has the same
+// minimal polynomial as the whole state since φ is irreducible
+fn characteristic_polynomial<P: MtParams>() -> Vec<u64> {
+    let degree = P::N * P::W - P::R;
+    let len = 2 * degree;
+
+    // The sequence is stored backwards, so each discrepancy is a dot product with the connection polynomial
+    let mut reversed = vec![0u64; len / 64 + 2];
+    let mut mt = MersenneTwister::<P>::new(P::Word::from_usize(5489));
+    let mut k = 0;
+    while k < len {
+        mt.twist();
+        for &word in mt.state.as_ref().iter().take(len - k) {
+            if (word >> (P::W - 1)) & P::Word::ONE != P::Word::ZERO {
+                let i = len - 1 - k;
+                reversed[i / 64] |= 1 << (i % 64);
+            }
+            k += 1;
+        }
+    }
+
+    let words = degree / 64 + 2;
+    let mut connection = vec![0u64; words];
+    connection[0] = 1;
+    let mut previous = connection.clone();
+    let (mut l, mut m) = (0, 1);
+
+    for i in 0..len {
+        let discrepancy = (0..=l / 64)
+            .map(|w| connection[w] & extract_word(&reversed, len - 1 - i + w * 64))
+            .fold(0, |acc, w| acc ^ w)
+            .count_ones()
+            % 2;
+
+        if discrepancy == 0 {
+            m += 1;
+        } else if 2 * l <= i {
+            let old = connection.clone();
+            xor_shifted(&mut connection, &previous, m);
+            l = i + 1 - l;
+            previous = old;
+            m = 1;
+        } else {
+            xor_shifted(&mut connection, &previous, m);
+            m += 1;
+        }
+    }
+    assert_eq!(l, degree);
+
+    // The connection polynomial is the characteristic polynomial reversed
+    let mut poly = vec![0u64; words];
+    for i in (0..=degree).filter(|&i| bit(&connection, degree - i)) {
+        poly[i / 64] |= 1 << (i % 64);
+    }
+    poly
+}
+
+// Arithmetic modulo a fixed polynomial
+struct Modulus {
+    degree: usize,
+    // Words in a reduced polynomial
+    words: usize,
+    // The modulus shifted left by 0 to 63 bits, so reducing never shifts
+    shifted: Vec<Vec<u64>>,
+}
+
+impl Modulus {
+    fn new(poly: Vec<u64>) -> Modulus {
+        let degree = (0..poly.len() * 64).rev().find(|&i| bit(&poly, i)).unwrap();
+        let shifted = (0..64)
+            .map(|shift| {
+                let mut s = vec![0u64; poly.len() + 1];
+                xor_shifted(&mut s, &poly, shift);
+                s
+            })
+            .collect();
+
+        Modulus {
+            degree,
+            words: degree / 64 + 1,
+            shifted,
+        }
+    }
+
+    fn square(&self, poly: &[u64]) -> Vec<u64> {
+        // Squaring over GF(2) just spreads the bits out
+        let mut squared = vec![0u64; poly.len() * 2 + 1];
+        for (i, &word) in poly.iter().enumerate() {
+            squared[2 * i] = spread(word as u32);
+            squared[2 * i + 1] = spread((word >> 32) as u32);
+        }
+
+        self.reduce(&mut squared);
+        squared.truncate(self.words);
+        squared
+    }
+
+    fn mul_x(&self, poly: &mut [u64]) {
+        let mut carry = 0;
+        for word in poly.iter_mut() {
+            let next_carry = *word >> 63;
+            *word = (*word << 1) | carry;
+            carry = next_carry;
+        }
+
+        if bit(poly, self.degree) {
+            for (word, m) in poly.iter_mut().zip(&self.shifted[0]) {
+                *word ^= m;
+            }
+        }
+    }
+
+    fn reduce(&self, poly: &mut [u64]) {
+        for i in (self.degree..poly.len() * 64).rev() {
+            if bit(poly, i) {
+                let shift = i - self.degree;
+                for (word, m) in poly[shift / 64..].iter_mut().zip(&self.shifted[shift % 64]) {
+                    *word ^= m;
+                }
+            }
+        }
+    }
+}
+
+fn bit(poly: &[u64], i: usize) -> bool {
+    poly.get(i / 64)
+        .is_some_and(|word| (word >> (i % 64)) & 1 == 1)
+}
+
+// The 64 bits starting at bit i, with zeros past the end
+fn extract_word(bits: &[u64], i: usize) -> u64 {
+    let (word, shift) = (i / 64, i % 64);
+    let low = bits.get(word).copied().unwrap_or(0) >> shift;
+    if shift == 0 {
+        low
+    } else {
+        low | (bits.get(word + 1).copied().unwrap_or(0) << (64 - shift))
+    }
+}
+
+// dest ^= src << shift, dropping anything past the end of dest
+fn xor_shifted(dest: &mut [u64], src: &[u64], shift: usize) {
+    let (words, bits) = (shift / 64, shift % 64);
+    for (i, &word) in src.iter().enumerate() {
+        if let Some(d) = dest.get_mut(i + words) {
+            *d ^= word << bits;
+        }
+        if bits != 0 {
+            if let Some(d) = dest.get_mut(i + words + 1) {
+                *d ^= word >> (64 - bits);
+            }
+        }
+    }
+}
+
+// Moves bit i to bit 2i
+fn spread(x: u32) -> u64 {
+    let mut x = x as u64;
+    x = (x | (x << 16)) & 0x0000FFFF0000FFFF;
+    x = (x | (x << 8)) & 0x00FF00FF00FF00FF;
+    x = (x | (x << 4)) & 0x0F0F0F0F0F0F0F0F;
+    x = (x | (x << 2)) & 0x3333333333333333;
+    x = (x | (x << 1)) & 0x5555555555555555;
+    x
+}
@@ -0,0 +1,92 @@
+use rand::{thread_rng, Rng};
+
+use super::{
+    time_seed::{self, Clock},
+    MT19937,
+};
+use crate::xor;
+
+/* ======== MT19937 STREAM CIPHER ======== */
+pub fn encrypt_mt19937(plaintext: &[u8], seed: u16) -> Vec<u8> {
+    let keystream: Vec<u8> = keystream(seed.into()).take(plaintext.len()).collect();
+
+    xor::xor(plaintext, &keystream)
+}
+
+// Decryption and encryption are the same operation, as in CTR mode
+pub fn decrypt_mt19937(ciphertext: &[u8], seed: u16) -> Vec<u8> {
+    encrypt_mt19937(ciphertext, seed)
+}
+
+// Each output gives 4 bytes of keystream, least significant first
+fn keystream(seed: u32) -> impl Iterator<Item = u8> {
+    MT19937::new(seed).flat_map(u32::to_le_bytes)
+}
+
+/* ======== SEED RECOVERY ======== */
+/// Finds the seed from a ciphertext whose plaintext ends with `known`.
+/// A 16-bit seed is small enough to try them all.
+///
+/// ```
+/// use cryptopals::mt19937::stream;
+///
+/// let plaintext = [b"random prefix".as_slice(), &[b'A'; 14]].concat();
+/// let ciphertext = stream::encrypt_mt19937(&plaintext, 12345);
+///
+/// assert_eq!(stream::recover_mt19937_seed(&ciphertext, &[b'A'; 14]), Some(12345));
+/// ```
+pub fn recover_mt19937_seed(ciphertext: &[u8], known: &[u8]) -> Option<u16> {
+    if known.len() > ciphertext.len() {
+        return None;
+    }
+    let offset = ciphertext.len() - known.len();
+
+    (0..=u16::MAX).find(|&seed| {
+        keystream(seed.into())
+            .skip(offset)
+            .zip(&ciphertext[offset..])
+            .map(|(k, c)| k ^ c)
+            .eq(known.iter().copied())
+    })
+}
+
+// Prepends a random number of random bytes to the plaintext, then encrypts with a secret seed
+pub fn gen_mt19937_prefix_oracle() -> impl Fn(&[u8]) -> Vec<u8> {
+    let mut rng = thread_rng();
+    let seed: u16 = rng.gen();
+    let prefix: Vec<u8> = (0..rng.gen_range(5..=40)).map(|_| rng.gen()).collect();
+
+    move |plaintext: &[u8]| {
+        let mut input = prefix.clone();
+        input.extend_from_slice(plaintext);
+        encrypt_mt19937(&input, seed)
+    }
+}
+
+/* ======== PASSWORD RESET TOKENS ======== */
+const TOKEN_LEN: usize = 16;
+
+// A token from the keystream of an MT19937 seeded with the current time
+pub fn password_reset_token(clock: &impl Clock) -> Vec<u8> {
+    keystream(clock.now()).take(TOKEN_LEN).collect()
+}
+
+/// Checks whether the token came from an MT19937 seeded with a time within `window` seconds of now,
+/// before or after.
+///
+/// ```
+/// use cryptopals::mt19937::{stream, time_seed::{Clock, SimulatedClock}};
+///
+/// let clock = SimulatedClock::new(1_700_000_000);
+/// let token = stream::password_reset_token(&clock);
+/// clock.sleep(300);
+///
+/// assert!(stream::is_time_seeded_token(&token, &clock, 3600));
+/// assert!(!stream::is_time_seeded_token(&[0; 16], &clock, 3600));
+/// ```
+pub fn is_time_seeded_token(token: &[u8], clock: &impl Clock, window: u32) -> bool {
+    time_seed::find_time_seed(clock, window, |seed| {
+        keystream(seed).take(token.len()).eq(token.iter().copied())
+    })
+    .is_some()
+}
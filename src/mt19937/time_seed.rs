@@ -0,0 +1,140 @@
+use std::{
+    cell::Cell,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use rand::{thread_rng, Rng};
+
+use super::MT19937;
+
+/* ======== CLOCKS ======== */
+// Unix time in seconds, swappable so that waiting can be simulated
+pub trait Clock {
+    fn now(&self) -> u32;
+
+    fn sleep(&self, secs: u32);
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u32 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as u32
+    }
+
+    fn sleep(&self, secs: u32) {
+        thread::sleep(Duration::from_secs(secs.into()));
+    }
+}
+
+// Sleeping just moves the time forward, so nothing actually waits
+// The time stops at u32::MAX rather than wrapping back to 1970
+pub struct SimulatedClock {
+    now: Cell<u32>,
+}
+
+impl SimulatedClock {
+    pub fn new(now: u32) -> SimulatedClock {
+        SimulatedClock {
+            now: Cell::new(now),
+        }
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> u32 {
+        self.now.get()
+    }
+
+    fn sleep(&self, secs: u32) {
+        self.now.set(self.now.get().saturating_add(secs));
+    }
+}
+
+/* ======== TIME SEED RECOVERY ======== */
+// Waits a while, seeds with the current time, waits again, then returns the first output
+pub fn time_seeded_output(clock: &impl Clock) -> u32 {
+    let mut rng = thread_rng();
+
+    clock.sleep(rng.gen_range(40..=1000));
+    let mut mt = MT19937::new(clock.now());
+    clock.sleep(rng.gen_range(40..=1000));
+
+    mt.next().unwrap()
+}
+
+/// Finds the timestamp within `window` seconds either side of now that seeds a generator with the
+/// given first output. Looking ahead as well as back covers a victim whose clock runs fast.
+///
+/// ```
+/// use cryptopals::mt19937::{time_seed::{self, Clock, SimulatedClock}, MT19937};
+///
+/// let clock = SimulatedClock::new(1_700_000_000);
+/// let output = time_seed::time_seeded_output(&clock);
+///
+/// let seed = time_seed::crack_time_seed_with_clock(&clock, output, 3600).unwrap();
+/// assert_eq!(MT19937::new(seed).next(), Some(output));
+///
+/// // Seeded by a clock ten minutes ahead of ours
+/// let skewed = SimulatedClock::new(1_700_000_600);
+/// let output = time_seed::time_seeded_output(&skewed);
+///
+/// let clock = SimulatedClock::new(1_700_000_000);
+/// let seed = time_seed::crack_time_seed_with_clock(&clock, output, 3600).unwrap();
+/// assert_eq!(MT19937::new(seed).next(), Some(output));
+/// assert!(seed > clock.now());
+/// ```
+pub fn crack_time_seed(first_output: u32, window: u32) -> Option<u32> {
+    crack_time_seed_with_clock(&SystemClock, first_output, window)
+}
+
+pub fn crack_time_seed_with_clock(
+    clock: &impl Clock,
+    first_output: u32,
+    window: u32,
+) -> Option<u32> {
+    find_time_seed(clock, window, |seed| {
+        MT19937::new(seed).next() == Some(first_output)
+    })
+}
+
+// Searches the timestamps within window seconds of now, either way, for one that is_seed accepts
+pub fn find_time_seed(
+    clock: &impl Clock,
+    window: u32,
+    is_seed: impl Fn(u32) -> bool + Sync,
+) -> Option<u32> {
+    let now = clock.now();
+    let (start, end) = (now.saturating_sub(window), now.saturating_add(window));
+
+    // Split the window between the cores, stopping all of them once one finds the seed
+    let threads = thread::available_parallelism().map_or(1, |n| n.get()) as u32;
+    let chunk = ((end - start) / threads).saturating_add(1);
+    let found = AtomicBool::new(false);
+
+    thread::scope(|s| {
+        let workers: Vec<_> = (0..threads)
+            .map(|i| {
+                let (found, is_seed) = (&found, &is_seed);
+                s.spawn(move || {
+                    let from = start.saturating_add(i.saturating_mul(chunk));
+                    let to = from.saturating_add(chunk - 1).min(end);
+
+                    (from..=to)
+                        .take_while(|_| !found.load(Ordering::Relaxed))
+                        .find(|&seed| is_seed(seed))
+                        .inspect(|_| found.store(true, Ordering::Relaxed))
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .find_map(|worker| worker.join().unwrap())
+    })
+}
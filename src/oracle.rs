@@ -0,0 +1,236 @@
+pub mod http;
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use crate::error::Error;
+
+/* ======== ORACLES ======== */
+// What the attacks are allowed to ask of their victims
+// Plain closures of the right shape are oracles too
+// An oracle that can't answer, being out of budget or out of reach, says so with an error
+pub trait EncryptionOracle {
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, Error>;
+}
+
+pub trait PaddingOracle {
+    fn check_padding(&self, ciphertext: &[u8]) -> Result<bool, Error>;
+}
+
+// Decryption errors are part of the answer, as they may leak information
+pub trait DecryptionOracle {
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, Error>;
+}
+
+impl<F: Fn(&[u8]) -> Vec<u8>> EncryptionOracle for F {
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(self(plaintext))
+    }
+}
+
+impl<F: Fn(&[u8]) -> bool> PaddingOracle for F {
+    fn check_padding(&self, ciphertext: &[u8]) -> Result<bool, Error> {
+        Ok(self(ciphertext))
+    }
+}
+
+impl<F: Fn(&[u8]) -> Result<Vec<u8>, Error>> DecryptionOracle for F {
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+        self(ciphertext)
+    }
+}
+
+/* ======== TRANSCRIPTS ======== */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Response {
+    Ciphertext(Vec<u8>),
+    ValidPadding(bool),
+    Plaintext(Result<Vec<u8>, Error>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Query {
+    pub input: Vec<u8>,
+    pub response: Response,
+}
+
+/// Wraps an oracle, counting and recording every query, from any number of threads.
+/// With a budget, every query past it fails with [`Error::QueryBudgetExceeded`].
+///
+/// ```
+/// use cryptopals::{aes::cbc::padding_oracle, oracle::{Metered, Replay}, Error};
+///
+/// let (ciphertext, iv, oracle) = padding_oracle::gen_aes_cbc_padding_oracle(b"YELLOW SUBMARINE");
+/// let metered = Metered::new(&oracle);
+/// let recovered = padding_oracle::cbc_padding_oracle_attack(&ciphertext, &iv, &metered)?;
+/// assert!(metered.queries() > 0);
+/// println!("{:.3} bytes per query", metered.bytes_per_query(recovered.len()));
+///
+/// // The same attack can be run again without the oracle
+/// let replay = Replay::new(metered.into_transcript());
+/// assert_eq!(padding_oracle::cbc_padding_oracle_attack(&ciphertext, &iv, &replay)?, recovered);
+///
+/// // A budget too small for the attack stops it, rather than the process
+/// let metered = Metered::with_budget(&oracle, 100);
+/// assert_eq!(
+///     padding_oracle::cbc_padding_oracle_attack(&ciphertext, &iv, &metered),
+///     Err(Error::QueryBudgetExceeded { budget: 100 })
+/// );
+/// # Ok::<(), cryptopals::Error>(())
+/// ```
+pub struct Metered<O> {
+    inner: O,
+    budget: Option<usize>,
+    // Queries started, which may be ahead of the transcript while others are in flight
+    started: AtomicUsize,
+    transcript: Mutex<Vec<Query>>,
+}
+
+impl<O> Metered<O> {
+    pub fn new(inner: O) -> Metered<O> {
+        Metered {
+            inner,
+            budget: None,
+            started: AtomicUsize::new(0),
+            transcript: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn with_budget(inner: O, budget: usize) -> Metered<O> {
+        Metered {
+            budget: Some(budget),
+            ..Metered::new(inner)
+        }
+    }
+
+    pub fn queries(&self) -> usize {
+        self.transcript.lock().unwrap().len()
+    }
+
+    pub fn bytes_per_query(&self, bytes_recovered: usize) -> f64 {
+        bytes_recovered as f64 / self.queries() as f64
+    }
+
+    pub fn transcript(&self) -> Vec<Query> {
+        self.transcript.lock().unwrap().clone()
+    }
+
+    pub fn into_transcript(self) -> Vec<Query> {
+        self.transcript.into_inner().unwrap()
+    }
+
+    fn record(
+        &self,
+        input: &[u8],
+        query: impl FnOnce(&O) -> Result<Response, Error>,
+    ) -> Result<Response, Error> {
+        // Each query takes its place in the budget up front, so concurrent ones can't overshoot it
+        let started = self.started.fetch_add(1, Ordering::Relaxed);
+        if let Some(budget) = self.budget {
+            if started >= budget {
+                return Err(Error::QueryBudgetExceeded { budget });
+            }
+        }
+
+        // Queries the inner oracle couldn't answer have nothing to record
+        let response = query(&self.inner)?;
+        self.transcript.lock().unwrap().push(Query {
+            input: input.to_vec(),
+            response: response.clone(),
+        });
+
+        Ok(response)
+    }
+}
+
+impl<O: EncryptionOracle> EncryptionOracle for Metered<O> {
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        match self.record(plaintext, |oracle| {
+            Ok(Response::Ciphertext(oracle.encrypt(plaintext)?))
+        })? {
+            Response::Ciphertext(ciphertext) => Ok(ciphertext),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<O: PaddingOracle> PaddingOracle for Metered<O> {
+    fn check_padding(&self, ciphertext: &[u8]) -> Result<bool, Error> {
+        match self.record(ciphertext, |oracle| {
+            Ok(Response::ValidPadding(oracle.check_padding(ciphertext)?))
+        })? {
+            Response::ValidPadding(valid) => Ok(valid),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<O: DecryptionOracle> DecryptionOracle for Metered<O> {
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+        match self.record(ciphertext, |oracle| {
+            Ok(Response::Plaintext(oracle.decrypt(ciphertext)))
+        })? {
+            Response::Plaintext(plaintext) => plaintext,
+            _ => unreachable!(),
+        }
+    }
+}
+
+// Answers from a recorded transcript, in any order
+// Anything it hasn't seen, or saw asked of a different kind of oracle, is an UnrecordedQuery
+pub struct Replay {
+    responses: HashMap<Vec<u8>, Response>,
+}
+
+impl Replay {
+    pub fn new(transcript: Vec<Query>) -> Replay {
+        Replay {
+            responses: transcript
+                .into_iter()
+                .map(|query| (query.input, query.response))
+                .collect(),
+        }
+    }
+
+    fn lookup(&self, input: &[u8]) -> Option<&Response> {
+        self.responses.get(input)
+    }
+}
+
+fn unrecorded(input: &[u8]) -> Error {
+    Error::UnrecordedQuery {
+        input: input.to_vec(),
+    }
+}
+
+impl EncryptionOracle for Replay {
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        match self.lookup(plaintext) {
+            Some(Response::Ciphertext(ciphertext)) => Ok(ciphertext.clone()),
+            _ => Err(unrecorded(plaintext)),
+        }
+    }
+}
+
+impl PaddingOracle for Replay {
+    fn check_padding(&self, ciphertext: &[u8]) -> Result<bool, Error> {
+        match self.lookup(ciphertext) {
+            Some(Response::ValidPadding(valid)) => Ok(*valid),
+            _ => Err(unrecorded(ciphertext)),
+        }
+    }
+}
+
+impl DecryptionOracle for Replay {
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+        match self.lookup(ciphertext) {
+            Some(Response::Plaintext(plaintext)) => plaintext.clone(),
+            _ => Err(unrecorded(ciphertext)),
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use std::{
+    io,
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::PaddingOracle;
+use crate::{error::Error, http, util};
+
+/* ======== HTTP PADDING ORACLE ======== */
+const DEFAULT_RETRIES: usize = 3;
+const FIRST_BACKOFF: Duration = Duration::from_millis(100);
+
+/// A padding oracle behind an HTTP endpoint.
+/// Each query is sent in hex in place of `{token}` in the URL template, and/or in a cookie,
+/// and the response status is passed to `is_valid`.
+/// Failed requests and 429s are retried with exponential backoff, and requests from all threads
+/// are spaced at least the rate limit apart. Once the retries run out the query fails with
+/// [`Error::OracleUnavailable`], which the attacks pass on.
+///
+/// ```
+/// use cryptopals::{
+///     aes::{self, cbc::padding_oracle::{self, server::PaddingOracleServer}},
+///     oracle::http::HttpPaddingOracle,
+///     util, Error,
+/// };
+///
+/// let server = PaddingOracleServer::bind("127.0.0.1:0", b"Cooking MC's like a pound of bacon")?;
+/// let token = util::hex_to_bytes(&server.token()).unwrap();
+/// let addr = server.spawn()?;
+///
+/// let url = format!("http://{}/check?token={{token}}", addr);
+/// let oracle = HttpPaddingOracle::new(&url, |status| status == 200).with_retries(5);
+///
+/// let (iv, ciphertext) = token.split_at(16);
+/// let (recovered, _) = padding_oracle::cbc_padding_oracle_attack_parallel(ciphertext, iv, &oracle)?;
+/// assert_eq!(aes::strip_pkcs7(&recovered)?, b"Cooking MC's like a pound of bacon");
+///
+/// // Nothing listening is an error from the attack, once the retries are spent
+/// let url = "http://127.0.0.1:1/check?token={token}";
+/// let oracle = HttpPaddingOracle::new(url, |status| status == 200).with_retries(0);
+/// let result = padding_oracle::cbc_padding_oracle_attack(ciphertext, iv, &oracle);
+/// assert!(matches!(result, Err(Error::OracleUnavailable { .. })));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct HttpPaddingOracle<F> {
+    url_template: String,
+    cookie: Option<String>,
+    is_valid: F,
+    retries: usize,
+    rate_limit: Duration,
+    next_request: Mutex<Instant>,
+}
+
+impl<F: Fn(u16) -> bool> HttpPaddingOracle<F> {
+    pub fn new(url_template: &str, is_valid: F) -> Self {
+        HttpPaddingOracle {
+            url_template: url_template.to_string(),
+            cookie: None,
+            is_valid,
+            retries: DEFAULT_RETRIES,
+            rate_limit: Duration::ZERO,
+            next_request: Mutex::new(Instant::now()),
+        }
+    }
+
+    // Also sends the token as the named cookie
+    pub fn with_cookie(mut self, name: &str) -> Self {
+        self.cookie = Some(name.to_string());
+        self
+    }
+
+    pub fn with_retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    // Minimum time between the starts of any two requests
+    pub fn with_rate_limit(mut self, interval: Duration) -> Self {
+        self.rate_limit = interval;
+        self
+    }
+
+    fn request(&self, token: &str) -> io::Result<u16> {
+        let url = self.url_template.replace("{token}", token);
+        let cookie = self
+            .cookie
+            .as_ref()
+            .map(|name| format!("{}={}", name, token));
+
+        match &cookie {
+            Some(cookie) => http::get(&url, &[("Cookie", cookie)]),
+            None => http::get(&url, &[]),
+        }
+    }
+
+    // Waits for this request's slot, holding the lock so others queue up behind it
+    fn throttle(&self) {
+        let mut next_request = self.next_request.lock().unwrap();
+        let now = Instant::now();
+        if *next_request > now {
+            thread::sleep(*next_request - now);
+        }
+        *next_request = Instant::now() + self.rate_limit;
+    }
+}
+
+impl<F: Fn(u16) -> bool> PaddingOracle for HttpPaddingOracle<F> {
+    fn check_padding(&self, ciphertext: &[u8]) -> Result<bool, Error> {
+        let token = util::bytes_to_hex(ciphertext);
+
+        let mut backoff = FIRST_BACKOFF;
+        let mut retries = 0;
+        loop {
+            self.throttle();
+            let error = match self.request(&token) {
+                Ok(429) => "429 Too Many Requests".to_string(),
+                Ok(status) => return Ok((self.is_valid)(status)),
+                Err(err) => err.to_string(),
+            };
+
+            if retries == self.retries {
+                return Err(Error::OracleUnavailable {
+                    reason: format!("request failed after {} retries: {}", retries, error),
+                });
+            }
+            retries += 1;
+            thread::sleep(backoff);
+            backoff *= 2;
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use crate::error::Error;
+
 /* ============ FREQUENCY ANALYSIS ============ */
 pub fn generate_frequency_map() -> HashMap<char, f64> {
     let monograms = include_str!("../assets/english_monograms.txt");
@@ -16,7 +18,7 @@ pub fn generate_frequency_map() -> HashMap<char, f64> {
 
     let total: f64 = freqs.values().sum();
     for (_, val) in freqs.iter_mut() {
-        *val = *val / total;
+        *val /= total;
     }
 
     freqs
@@ -24,7 +26,7 @@ pub fn generate_frequency_map() -> HashMap<char, f64> {
 
 pub fn calculate_monogram_fitness(str: &[u8], freqs: &HashMap<char, f64>) -> f64 {
     str.iter().fold(0f64, |acc, &b| {
-        let freq = freqs.get(&(b as char)).cloned().unwrap_or_else(|| {
+        let freq = freqs.get(&(b as char)).cloned().unwrap_or({
             if b == b' ' {
                 0.2 // approximate frequency of space
             } else {
@@ -36,13 +38,13 @@ pub fn calculate_monogram_fitness(str: &[u8], freqs: &HashMap<char, f64>) -> f64
     })
 }
 
-// Unused, prefer monogram analysis for determining fitness of text
-fn calculate_chi_squared(str: &[u8], freqs: &HashMap<char, f64>) -> f64 {
+// Prefer monogram analysis for determining fitness of text, this is kept for comparison
+pub fn calculate_chi_squared(str: &[u8], freqs: &HashMap<char, f64>) -> f64 {
     let str_filtered: Vec<u8> = str
         .iter()
-        .filter(|&&c| (c >= b'a' && c <= b'z') || (c >= b'A' && c <= b'Z'))
+        .filter(|&&c| c.is_ascii_lowercase() || c.is_ascii_uppercase())
         .map(|&c| {
-            if c >= b'A' && c <= b'Z' {
+            if c.is_ascii_uppercase() {
                 c - b'A' + b'a'
             } else {
                 c
@@ -66,24 +68,25 @@ fn calculate_chi_squared(str: &[u8], freqs: &HashMap<char, f64>) -> f64 {
 }
 
 /* ============ HEX ============ */
-pub fn hex_to_bytes(hex: &str) -> Vec<u8> {
-    if hex.len() % 2 != 0 {
-        panic!("Hex string does not have even length")
-    }
-
+pub fn hex_to_bytes(hex: &str) -> Result<Vec<u8>, Error> {
     let half_bytes: Vec<u8> = hex
-        .to_lowercase()
         .bytes()
-        .map(|b| {
-            if b >= 48 && b <= 57 {
-                b - 48
-            } else {
-                b - 97 + 10
-            }
+        .enumerate()
+        .map(|(position, b)| match b {
+            b'0'..=b'9' => Ok(b - b'0'),
+            b'a'..=b'f' => Ok(b - b'a' + 10),
+            b'A'..=b'F' => Ok(b - b'A' + 10),
+            _ => Err(Error::InvalidHex { position }),
         })
-        .collect();
+        .collect::<Result<_, _>>()?;
+
+    if !half_bytes.len().is_multiple_of(2) {
+        return Err(Error::InvalidHex {
+            position: half_bytes.len(),
+        });
+    }
 
-    half_bytes.chunks(2).map(|b| b[0] * 16 + b[1]).collect()
+    Ok(half_bytes.chunks(2).map(|b| b[0] * 16 + b[1]).collect())
 }
 
 pub fn bytes_to_hex(bytes: &[u8]) -> String {
@@ -111,44 +114,71 @@ fn int_to_hex(i: u8) -> char {
 }
 
 /* ============ BASE64 ============ */
-pub fn base64_to_bytes(base64: &str) -> Vec<u8> {
-    assert!(base64.len() % 4 == 0);
+/// Decodes padded base64. Error positions are byte offsets into the string.
+///
+/// ```
+/// use cryptopals::{util, Error};
+///
+/// assert_eq!(util::base64_to_bytes("SSdt")?, b"I'm");
+/// assert_eq!(util::base64_to_bytes("SSc=")?, b"I'");
+///
+/// // Padding only goes at the end, and is never more than two characters
+/// assert_eq!(util::base64_to_bytes("SS=dt"), Err(Error::InvalidBase64 { position: 2 }));
+/// assert_eq!(util::base64_to_bytes("S==="), Err(Error::InvalidBase64 { position: 3 }));
+/// assert_eq!(util::base64_to_bytes("é=="), Err(Error::InvalidBase64 { position: 0 }));
+/// assert_eq!(util::base64_to_bytes("SSdtI"), Err(Error::InvalidBase64 { position: 5 }));
+/// # Ok::<(), cryptopals::Error>(())
+/// ```
+pub fn base64_to_bytes(base64: &str) -> Result<Vec<u8>, Error> {
+    // Up to two = of padding, and only at the end
+    let data = base64.trim_end_matches('=');
+    if base64.len() - data.len() > 2 {
+        return Err(Error::InvalidBase64 {
+            position: data.len() + 2,
+        });
+    }
+
+    let binary: Vec<bool> = data
+        .char_indices()
+        .map(|(position, c)| base64_to_binary(c).ok_or(Error::InvalidBase64 { position }))
+        .collect::<Result<Vec<Vec<bool>>, _>>()?
+        .concat();
 
-    let binary: Vec<bool> = base64.chars().flat_map(base64_to_binary).collect();
+    if !base64.len().is_multiple_of(4) {
+        return Err(Error::InvalidBase64 {
+            position: base64.len(),
+        });
+    }
 
-    binary.chunks_exact(8).map(binary_to_byte).collect()
+    Ok(binary.chunks_exact(8).map(binary_to_byte).collect())
 }
 
 pub fn bytes_to_base64(bytes: &[u8]) -> String {
     let bits: Vec<bool> = bytes.iter().flat_map(|&b| byte_to_binary(b)).collect();
     let mut base64: String = bits.chunks(6).map(binary_to_base64).collect();
-    while base64.len() % 4 != 0 {
+    while !base64.len().is_multiple_of(4) {
         base64 += "=";
     }
 
     base64
 }
 
-fn base64_to_binary(c: char) -> Vec<bool> {
-    if c == '=' {
-        return vec![];
-    }
-
-    let byte = if c >= 'A' && c <= 'Z' {
+fn base64_to_binary(c: char) -> Option<Vec<bool>> {
+    let byte = if c.is_ascii_uppercase() {
         c as u8 - b'A'
-    } else if c >= 'a' && c <= 'z' {
+    } else if c.is_ascii_lowercase() {
         c as u8 - b'a' + 26
-    } else if c >= '0' && c <= '9' {
+    } else if c.is_ascii_digit() {
         c as u8 - b'0' + 52
     } else if c == '+' {
         62
     } else if c == '/' {
         63
     } else {
-        panic!("Invalid base64 char")
+        return None;
     };
 
-    byte_to_binary(byte).into_iter().skip(2).collect()
+    Some(byte_to_binary(byte).into_iter().skip(2).collect())
 }
 
 fn binary_to_base64(binary: &[bool]) -> char {
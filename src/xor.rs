@@ -1,4 +1,4 @@
-use crate::util;
+use crate::{error::Error, util};
 
 pub fn calculate_edit_distance(b1: &[u8], b2: &[u8]) -> usize {
     b1.iter()
@@ -9,8 +9,13 @@ pub fn calculate_edit_distance(b1: &[u8], b2: &[u8]) -> usize {
 }
 
 // Using Kasiski examination
-pub fn find_keysize_candidates(encoded: &[u8]) -> Vec<usize> {
-    let mut keysizes: Vec<(usize, f64)> = (2..=40)
+pub fn find_keysize_candidates(encoded: &[u8]) -> Result<Vec<usize>, Error> {
+    if encoded.is_empty() {
+        return Err(Error::EmptyInput);
+    }
+
+    // Keysizes longer than the input have no block to compare
+    let mut keysizes: Vec<(usize, f64)> = (2..=40.min(encoded.len()))
         .map(|size| {
             let mut edit_distance = 0;
             let blocks = encoded.len() / size;
@@ -21,9 +26,9 @@ pub fn find_keysize_candidates(encoded: &[u8]) -> Vec<usize> {
             (size, edit_distance as f64 / blocks as f64 / size as f64)
         })
         .collect();
-    keysizes.sort_by(|(_, d1), (_, d2)| d1.partial_cmp(d2).unwrap());
+    keysizes.sort_by(|(_, d1), (_, d2)| d1.total_cmp(d2));
 
-    keysizes.iter().map(|&(size, _)| size).collect()
+    Ok(keysizes.iter().map(|&(size, _)| size).collect())
 }
 
 /* ============ XOR ============ */
@@ -32,7 +37,28 @@ pub struct RepeatingXORDecryptResult {
     pub message: Vec<u8>,
 }
 
-pub fn decrypt_repeating_key_xor(msg: &[u8], keysize: usize) -> RepeatingXORDecryptResult {
+/// ```
+/// use cryptopals::{xor, Error};
+///
+/// let plaintext = b"Burning 'em, if you ain't quick and nimble\nI go crazy when I hear a cymbal";
+/// let ciphertext = xor::repeating_key_xor(plaintext, b"ICE");
+/// assert_eq!(xor::decrypt_repeating_key_xor(&ciphertext, 3)?.key, b"ICE");
+///
+/// assert_eq!(xor::find_keysize_candidates(&[]), Err(Error::EmptyInput));
+/// assert!(xor::decrypt_repeating_key_xor(&[], 3).is_err());
+/// # Ok::<(), cryptopals::Error>(())
+/// ```
+pub fn decrypt_repeating_key_xor(
+    msg: &[u8],
+    keysize: usize,
+) -> Result<RepeatingXORDecryptResult, Error> {
+    if msg.is_empty() {
+        return Err(Error::EmptyInput);
+    }
+    if keysize == 0 {
+        return Err(Error::InvalidKeyLength { len: keysize });
+    }
+
     let mut transposed: Vec<Vec<u8>> = vec![vec![]; keysize];
 
     for (i, &b) in msg.iter().enumerate() {
@@ -46,15 +72,11 @@ pub fn decrypt_repeating_key_xor(msg: &[u8], keysize: usize) -> RepeatingXORDecr
 
     let message = repeating_key_xor(msg, &key);
 
-    RepeatingXORDecryptResult { key, message }
+    Ok(RepeatingXORDecryptResult { key, message })
 }
 
 pub fn repeating_key_xor(msg: &[u8], key: &[u8]) -> Vec<u8> {
-    let key: Vec<u8> = vec![key.to_vec(); (msg.len() - 1) / key.len() + 1]
-        .into_iter()
-        .flatten()
-        .take(msg.len())
-        .collect();
+    let key: Vec<u8> = key.iter().cycle().take(msg.len()).copied().collect();
 
     xor(msg, &key)
 }
@@ -65,7 +87,7 @@ pub struct XORDecryptResult {
     pub message: Vec<u8>,
 }
 
-pub fn detect_single_byte_xor(encrypted_messages: &Vec<Vec<u8>>) -> Option<XORDecryptResult> {
+pub fn detect_single_byte_xor(encrypted_messages: &[Vec<u8>]) -> Option<XORDecryptResult> {
     let freqs = util::generate_frequency_map();
 
     let decrypted = encrypted_messages
@@ -82,8 +104,8 @@ pub fn detect_single_byte_xor(encrypted_messages: &Vec<Vec<u8>>) -> Option<XORDe
     res.cloned()
 }
 
-// Unused
-fn decrypt_single_byte_xor_options(encrypted: &[u8]) -> Vec<XORDecryptResult> {
+// Returns the 3 most likely decryptions, best first
+pub fn decrypt_single_byte_xor_options(encrypted: &[u8]) -> Vec<XORDecryptResult> {
     let freqs = util::generate_frequency_map();
 
     let mut decrypted: Vec<(u8, Vec<u8>)> = (u8::MIN..=u8::MAX)